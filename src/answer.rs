@@ -0,0 +1,99 @@
+use crate::json::Json;
+use num::BigInt;
+use std::fmt::Display;
+
+// Value produced by a single part of a day.
+// Parts return it instead of printing, so the runner decides how to show it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Answer {
+    Int(i64),
+    BigInt(BigInt),
+    Text(String),
+}
+
+impl Display for Answer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Answer::Int(n) => write!(f, "{}", n),
+            Answer::BigInt(n) => write!(f, "{}", n),
+            Answer::Text(s) => write!(f, "{}", s),
+        }
+    }
+}
+
+// Small unsigned and signed numbers always fit into i64
+macro_rules! answer_from_int {
+    ($($t:ty),*) => {
+        $(
+            impl From<$t> for Answer {
+                fn from(n: $t) -> Self {
+                    Answer::Int(n as i64)
+                }
+            }
+        )*
+    };
+}
+
+answer_from_int!(i32, u32, i64);
+
+// Wide unsigned numbers fall back to BigInt when they do not fit into i64
+macro_rules! answer_from_wide_int {
+    ($($t:ty),*) => {
+        $(
+            impl From<$t> for Answer {
+                fn from(n: $t) -> Self {
+                    match i64::try_from(n) {
+                        Ok(n) => Answer::Int(n),
+                        Err(_) => Answer::BigInt(BigInt::from(n)),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+answer_from_wide_int!(u64, usize, u128, i128);
+
+impl From<BigInt> for Answer {
+    fn from(n: BigInt) -> Self {
+        Answer::BigInt(n)
+    }
+}
+
+impl From<String> for Answer {
+    fn from(s: String) -> Self {
+        Answer::Text(s)
+    }
+}
+
+impl From<&str> for Answer {
+    fn from(s: &str) -> Self {
+        Answer::Text(s.to_string())
+    }
+}
+
+// Numbers stay JSON numbers whatever their size, text becomes a string
+impl From<&Answer> for Json {
+    fn from(answer: &Answer) -> Self {
+        match answer {
+            Answer::Int(n) => Json::number(n),
+            Answer::BigInt(n) => Json::number(n),
+            Answer::Text(s) => Json::from(s.as_str()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wide_int_fallback() {
+        assert_eq!(Answer::Int(42), Answer::from(42u64));
+        assert_eq!(
+            Answer::BigInt(BigInt::from(u64::MAX)),
+            Answer::from(u64::MAX)
+        );
+        assert_eq!("18446744073709551615", Answer::from(u64::MAX).to_string());
+    }
+}
@@ -0,0 +1,100 @@
+use crate::error::Result;
+use crate::input;
+use crate::runner::PartFn;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+// Timings of a single run of a part: parsing is the time spent loading the input,
+// solving is everything else
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Timings {
+    pub parse: Duration,
+    pub solve: Duration,
+}
+
+impl Timings {
+    pub fn total(&self) -> Duration {
+        self.parse + self.solve
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct Summary {
+    pub min: Duration,
+    pub median: Duration,
+    pub p95: Duration,
+    pub max: Duration,
+}
+
+impl Summary {
+    // Percentiles use the nearest-rank method, so they are always one of the samples
+    pub fn of(samples: &[Duration]) -> Summary {
+        assert!(!samples.is_empty(), "Summary of no samples");
+        let mut sorted = samples.to_vec();
+        sorted.sort();
+        let percentile = |p: usize| sorted[(p * sorted.len()).div_ceil(100).max(1) - 1];
+        Summary {
+            min: sorted[0],
+            median: percentile(50),
+            p95: percentile(95),
+            max: sorted[sorted.len() - 1],
+        }
+    }
+}
+
+// Run the part `iterations` times, stopping at the first error
+pub fn bench(part_fn: PartFn, path: &Path, iterations: usize) -> Result<Vec<Timings>> {
+    let mut result = Vec::with_capacity(iterations);
+    for _ in 0..iterations {
+        input::take_parse_time();
+        let start = Instant::now();
+        part_fn(path)?;
+        let total = start.elapsed();
+        let parse = input::take_parse_time().min(total);
+        result.push(Timings {
+            parse,
+            solve: total - parse,
+        });
+    }
+    Ok(result)
+}
+
+pub fn print_report(timings: &[Timings]) {
+    println!(
+        "{:<6} {:>12} {:>12} {:>12} {:>12}",
+        "", "min", "median", "p95", "max"
+    );
+    let rows = [
+        ("parse", timings.iter().map(|t| t.parse).collect::<Vec<_>>()),
+        ("solve", timings.iter().map(|t| t.solve).collect()),
+        ("total", timings.iter().map(Timings::total).collect()),
+    ];
+    for (name, samples) in rows {
+        let s = Summary::of(&samples);
+        println!(
+            "{:<6} {:>12} {:>12} {:>12} {:>12}",
+            name,
+            format!("{:.3?}", s.min),
+            format!("{:.3?}", s.median),
+            format!("{:.3?}", s.p95),
+            format!("{:.3?}", s.max)
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_summary() {
+        let samples: Vec<Duration> = (1..=20).rev().map(Duration::from_millis).collect();
+        let s = Summary::of(&samples);
+        assert_eq!(Duration::from_millis(1), s.min);
+        assert_eq!(Duration::from_millis(10), s.median);
+        assert_eq!(Duration::from_millis(19), s.p95);
+        assert_eq!(Duration::from_millis(20), s.max);
+        let single = Summary::of(&[Duration::from_millis(7)]);
+        assert_eq!(Duration::from_millis(7), single.p95);
+    }
+}
@@ -0,0 +1,124 @@
+use std::collections::hash_map::RandomState;
+use std::collections::HashMap;
+use std::hash::{BuildHasher, Hash};
+
+// States of a simulation which ends up repeating itself: the states before
+// the loop followed by one pass through the loop
+#[derive(Debug, Clone)]
+pub struct Cycle<S> {
+    // step of the first state of the loop
+    pub start: usize,
+    // number of steps after which the loop repeats
+    pub period: usize,
+    states: Vec<S>,
+}
+
+impl<S: Eq + Hash> Cycle<S> {
+    // Step from the `initial` state until a state comes back.
+    // This only ends if the simulation has a finite number of states.
+    pub fn find(initial: S, step: impl FnMut(&S) -> S) -> Cycle<S> {
+        match explore(initial, step, usize::MAX) {
+            Walk::Looped(cycle) => cycle,
+            Walk::Reached(_) => unreachable!("No loop within usize::MAX steps"),
+        }
+    }
+}
+
+impl<S> Cycle<S> {
+    // State after `n` steps from the initial one
+    pub fn state_at(&self, n: usize) -> &S {
+        let idx = if n < self.start {
+            n
+        } else {
+            self.start + (n - self.start) % self.period
+        };
+        &self.states[idx]
+    }
+
+    // States before the loop and then the states of the loop
+    pub fn states(&self) -> &[S] {
+        &self.states
+    }
+
+    // States of the loop from the one at step `start`
+    pub fn loop_states(&self) -> &[S] {
+        &self.states[self.start..]
+    }
+}
+
+// State after `n` steps from the `initial` one, simulated only until it loops
+pub fn state_at<S: Clone + Eq + Hash>(initial: S, step: impl FnMut(&S) -> S, n: usize) -> S {
+    match explore(initial, step, n) {
+        Walk::Reached(state) => state,
+        Walk::Looped(cycle) => cycle.state_at(n).clone(),
+    }
+}
+
+enum Walk<S> {
+    // the state at the step limit, found before any repetition
+    Reached(S),
+    Looped(Cycle<S>),
+}
+
+// History of the states: the states are kept once, in the order they were seen,
+// and a hash map gives the steps of the states with each hash
+fn explore<S: Eq + Hash>(initial: S, mut step: impl FnMut(&S) -> S, limit: usize) -> Walk<S> {
+    let hasher = RandomState::new();
+    let mut seen: HashMap<u64, Vec<usize>> = HashMap::new();
+    let mut states = Vec::new();
+    let mut state = initial;
+    loop {
+        if states.len() == limit {
+            return Walk::Reached(state);
+        }
+        let hash = hasher.hash_one(&state);
+        let steps = seen.entry(hash).or_default();
+        if let Some(&start) = steps.iter().find(|&&idx| states[idx] == state) {
+            return Walk::Looped(Cycle {
+                start,
+                period: states.len() - start,
+                states,
+            });
+        }
+        steps.push(states.len());
+        let next = step(&state);
+        states.push(state);
+        state = next;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    #[test]
+    fn test_find() {
+        // 0 -> 1 -> 2 -> 3 -> 4 -> 2
+        let cycle = Cycle::find(0, |&x| if x == 4 { 2 } else { x + 1 });
+        assert_eq!((2, 3), (cycle.start, cycle.period));
+        assert_eq!(&[0, 1, 2, 3, 4], cycle.states());
+        assert_eq!(&[2, 3, 4], cycle.loop_states());
+        assert_eq!(&1, cycle.state_at(1));
+        assert_eq!(&4, cycle.state_at(1_000_000_000));
+    }
+
+    #[test]
+    fn test_state_at_agrees_with_simulation() {
+        let mut rng = Rng::new(14);
+        for _ in 0..100 {
+            // random function on a few states, which has to loop somewhere
+            let size = rng.range(1..20) as usize;
+            let table: Vec<usize> = (0..size).map(|_| rng.below(size)).collect();
+            let step = |&x: &usize| table[x];
+            let initial = rng.below(size);
+            let cycle = Cycle::find(initial, step);
+            let mut state = initial;
+            for n in 0..60 {
+                assert_eq!(&state, cycle.state_at(n));
+                assert_eq!(state, state_at(initial, step, n));
+                state = step(&state);
+            }
+        }
+    }
+}
@@ -1,16 +1,26 @@
+use crate::answer::Answer;
+use crate::error::{ParseError, Result};
+use crate::input;
+use crate::rng::Rng;
 use std::collections::HashMap;
-use std::fs::File;
-use std::io::{self, BufRead};
+use std::io::BufRead;
 use std::path::Path;
 
-pub fn part1(input_path: &Path) {
-    let input = parse_input(input_path, extract_num_simple).unwrap();
+pub fn part1(input_path: &Path) -> Result<Answer> {
+    let input = load_input(input_path, extract_num_simple)?;
     let result: u32 = input.iter().sum();
-    println!("Sum: {}", result);
+    Ok(result.into())
 }
 
-pub fn part2(input_path: &Path) {
-    let digit_values: HashMap<&str, u32> = HashMap::from([
+pub fn part2(input_path: &Path) -> Result<Answer> {
+    let input = load_input(input_path, extract_num_complex(digit_values()))?;
+    let result: u32 = input.iter().sum();
+    Ok(result.into())
+}
+
+// Digits and their spelled out names
+fn digit_values() -> HashMap<&'static str, u32> {
+    HashMap::from([
         ("0", 0),
         ("zero", 0),
         ("1", 1),
@@ -31,57 +41,84 @@ pub fn part2(input_path: &Path) {
         ("eight", 8),
         ("9", 9),
         ("nine", 9),
-    ]);
-    let input = parse_input(input_path, extract_num_complex(digit_values)).unwrap();
-    let result: u32 = input.iter().sum();
-    println!("Sum: {}", result);
+    ])
 }
 
-type Part1Input = Vec<u32>;
+pub type Part1Input = Vec<u32>;
 
-fn extract_num_simple(line: &str) -> u32 {
-    let first_digit = line.chars().find(|c| c.is_digit(10)).unwrap();
-    let last_digit = line.chars().rev().find(|c| c.is_digit(10)).unwrap();
-    let num_string = format!("{}{}", first_digit, last_digit);
-    let num = num_string
-        .parse()
-        .map_err(|_| io::Error::new(io::ErrorKind::Other, "Cannot parse number"));
-    return num.unwrap();
+pub fn extract_num_simple(line: &str) -> Result<u32, ParseError> {
+    let first_digit = line
+        .chars()
+        .find(|c| c.is_ascii_digit())
+        .ok_or_else(|| ParseError::new(line, line, "a digit"))?;
+    let last_digit = line.chars().rev().find(|c| c.is_ascii_digit()).unwrap();
+    Ok(first_digit.to_digit(10).unwrap() * 10 + last_digit.to_digit(10).unwrap())
 }
 
-fn extract_num_complex(digit_values: HashMap<&str, u32>) -> Box<dyn Fn(&str) -> u32 + '_> {
-    return Box::new(move |line| -> u32 {
+pub fn extract_num_complex(
+    digit_values: HashMap<&str, u32>,
+) -> impl Fn(&str) -> Result<u32, ParseError> + '_ {
+    move |line| {
         // for each digit string we search for its first and last index in line
         // then we take the value of first string and last one in each str
         let digit_indices: Vec<(&str, Option<usize>, Option<usize>)> = digit_values
-            .iter()
-            .map(|(dig_str, _)| (*dig_str, line.find(dig_str), line.rfind(dig_str)))
+            .keys()
+            .map(|dig_str| (*dig_str, line.find(dig_str), line.rfind(dig_str)))
             .collect();
         let min_digit = digit_indices
             .iter()
             .filter(|(_, idx, _)| idx.is_some())
-            .min_by_key(|(_, idx, _)| idx.unwrap_or(usize::MAX));
+            .min_by_key(|(_, idx, _)| idx.unwrap_or(usize::MAX))
+            .ok_or_else(|| ParseError::new(line, line, "a digit or a spelled digit"))?;
         let max_digit = digit_indices
             .iter()
             .filter(|(_, _, idx)| idx.is_some())
-            .max_by_key(|(_, _, idx)| idx.unwrap_or(usize::MIN));
-        let first_value = digit_values[min_digit.unwrap().0];
-        let last_value = digit_values[max_digit.unwrap().0];
-        first_value * 10 + last_value
-    });
+            .max_by_key(|(_, _, idx)| idx.unwrap_or(usize::MIN))
+            .unwrap();
+        let first_value = digit_values[min_digit.0];
+        let last_value = digit_values[max_digit.0];
+        Ok(first_value * 10 + last_value)
+    }
+}
+
+// `size` lines of letters and spelled out digits, each with at least one digit for part 1
+pub fn generate(rng: &mut Rng, size: usize) -> Vec<String> {
+    let mut words: Vec<&str> = digit_values().into_keys().collect();
+    words.sort();
+    (0..size)
+        .map(|_| {
+            let mut pieces: Vec<String> = (0..rng.range(0..6))
+                .map(|_| {
+                    if rng.chance(0.5) {
+                        rng.choose(&words).to_string()
+                    } else {
+                        let len = rng.range(1..5) as usize;
+                        rng.string("abcdefghijklmnopqrstuvwxyz", len)
+                    }
+                })
+                .collect();
+            let digit = rng.range(1..10).to_string();
+            pieces.insert(rng.below(pieces.len() + 1), digit);
+            pieces.concat()
+        })
+        .collect()
 }
 
-fn parse_input<F>(input_path: &Path, extractor: F) -> io::Result<Part1Input>
+// Lines are checked with the spelled out digits, the inputs of both parts follow that grammar
+pub fn check(input_path: &Path) -> Result<Vec<ParseError>> {
+    input::check_lines(input_path, extract_num_complex(digit_values()))
+}
+
+pub fn load_input<F>(input_path: &Path, extractor: F) -> Result<Part1Input>
 where
-    F: Fn(&str) -> u32,
+    F: Fn(&str) -> Result<u32, ParseError>,
 {
-    let file = File::open(input_path)?;
-    let buf_reader = io::BufReader::new(file);
-    let mut result = Vec::new();
-    for line in buf_reader.lines() {
-        let line = line?;
-        let num = extractor(&line);
-        result.push(num);
-    }
-    return Ok(result);
+    input::load(input_path, |reader| parse_input(reader, extractor))
+}
+
+pub fn parse_input<F>(reader: impl BufRead, extractor: F) -> Result<Part1Input>
+where
+    F: Fn(&str) -> Result<u32, ParseError>,
+{
+    input::parse_lines(reader, extractor)
 }
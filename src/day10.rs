@@ -1,18 +1,25 @@
+use crate::answer::Answer;
+use crate::error::{ParseError, Result};
+use crate::geometry::{Direction, Point};
+use crate::grid::Grid;
+use crate::input;
+use crate::rng::Rng;
+use crate::{debug, trace};
+use std::cmp::Ordering;
 use std::collections::HashMap;
-use std::fs::File;
-use std::io::{self, BufRead};
+use std::io::BufRead;
 use std::path::Path;
 
-pub fn part1(input_path: &Path) {
-    let input = load_input(input_path).unwrap();
+pub fn part1(input_path: &Path) -> Result<Answer> {
+    let input = load_input(input_path)?;
     let (s_row, s_col) = find_start(&input);
-    eprintln!("Found S position: {}, {}", &s_row, &s_col);
+    debug!("Found S position: {}, {}", &s_row, &s_col);
     let path = find_path(&input);
-    eprintln!("Full path: {:?}", &path);
-    println!("Result: {}", path.len() / 2);
+    debug!("Full path: {:?}", &path);
+    Ok((path.len() / 2).into())
 }
 
-pub fn part2(input_path: &Path) {
+pub fn part2(input_path: &Path) -> Result<Answer> {
     // the idea is to use scan-line polygon fill
     // we go in horizontal line until we encounter 'loop'
     // we have a flag which signifies that we are within polygon
@@ -21,15 +28,13 @@ pub fn part2(input_path: &Path) {
     // .|..|.|..|.
     // f|tt|f|tt|f
     // we need to remember the whole loop path to achieve that
-    let input = load_input(input_path).unwrap();
-    eprintln!("Image:");
-    for row in &input {
-        eprintln!("{}", row.iter().collect::<String>());
-    }
+    let input = load_input(input_path)?;
+    debug!("Image:\n{}", input);
     let path = find_path(&input);
-    eprintln!("Full path: {:?}", path);
+    debug!("Full path: {:?}", path);
     // now let's group borders by row: in map key is row number and value is list of columns at which borders are present
-    let mut loop_borders: HashMap<usize, Vec<(usize, char)>> = HashMap::with_capacity(input.len());
+    let mut loop_borders: HashMap<usize, Vec<(usize, char)>> =
+        HashMap::with_capacity(input.height());
     for (row, col, c) in path {
         loop_borders
             .entry(row)
@@ -63,14 +68,14 @@ pub fn part2(input_path: &Path) {
     // we should scan each row for loop elements
     // for each row
     for (row, col_data) in loop_borders_vec {
-        let row_line = String::from_iter(&input[row]);
-        eprintln!("Scan row {:02}: {}", row, row_line);
-        eprint!("             ");
+        let row_line = String::from_iter(input.row(row));
+        // the row with loop borders replaced by the inside flag and inner tiles marked with '@'
+        let mut scan_line = String::with_capacity(row_line.len());
         let mut borders = col_data.clone();
         borders.sort_by_key(|x| x.0);
         let mut flag = false;
         // I scan the whole row left to right
-        for j in 0..row_line.len() - 1 {
+        for (j, &c) in input.row(row).iter().enumerate().take(row_line.len() - 1) {
             // if current pipe is in the loop
             if let Some((_, pipe)) = borders.iter().find(|(col, _)| *col == j) {
                 match pipe {
@@ -82,18 +87,23 @@ pub fn part2(input_path: &Path) {
                     _ => {} // do nothing
                 }
                 let flag_c = if flag { '1' } else { '0' };
-                eprint!("{}", flag_c)
+                scan_line.push(flag_c);
             } else {
-                if flag && input[row][j] == '.' {
+                if flag && c == '.' {
                     // eprintln!(". within loop");
-                    eprint!("@");
+                    scan_line.push('@');
                     result += 1
                 } else {
-                    eprint!("{}", input[row][j]);
+                    scan_line.push(c);
                 }
             }
         }
-        eprintln!();
+        trace!(
+            "Scan row {:02}: {}\n             {}",
+            row,
+            row_line,
+            scan_line
+        );
         // for i in 1..borders.len() {
         //     flag = !flag;
         //     if flag {
@@ -111,23 +121,17 @@ pub fn part2(input_path: &Path) {
         //     }
         // }
     }
-    println!("Result: {}", result);
+    Ok(result.into())
 }
 
-fn find_start(input: &Vec<Vec<char>>) -> (usize, usize) {
-    for (i, row) in input.iter().enumerate() {
-        let found = row.iter().enumerate().find(|(_, c)| **c == 'S');
-        if found.is_some() {
-            return (i, found.unwrap().0);
-        }
-    }
-    unreachable!()
+pub fn find_start(input: &Grid<char>) -> (usize, usize) {
+    input.find(|c| *c == 'S').unwrap()
 }
 
-fn find_path(input: &Vec<Vec<char>>) -> Vec<(usize, usize, char)> {
+pub fn find_path(input: &Grid<char>) -> Vec<(usize, usize, char)> {
     let (s_row, s_col) = find_start(input);
-    let s_connections = connections(&input, s_row, s_col, &all_directions());
-    eprintln!("S connections: {:?}", s_connections);
+    let s_connections = connections(input, s_row, s_col, &Direction::ALL);
+    trace!("S connections: {:?}", s_connections);
     if s_connections.len() != 2 {
         panic!(
             "Unexpected: S should have 2 compatible connections, but has {}!",
@@ -138,12 +142,10 @@ fn find_path(input: &Vec<Vec<char>>) -> Vec<(usize, usize, char)> {
     let random_step = s_connections.iter().next().unwrap();
     let mut direction: Direction = *random_step.0;
     let mut curr_pipe: char = *random_step.1;
-    let mut next_row = s_row;
-    let mut next_col = s_col;
+    let mut next = Point::new(s_row, s_col);
     while curr_pipe != 'S' {
-        let (d_row, d_col) = delta(direction);
-        next_row = ((next_row as i16) + d_row) as usize;
-        next_col = ((next_col as i16) + d_col) as usize;
+        next = next.step(direction, input.bounds()).unwrap();
+        let (next_row, next_col) = next.into();
         full_path.push((next_row, next_col, curr_pipe));
         // eprintln!(
         //     "Chosen to connect on {} at {}, {}",
@@ -151,7 +153,7 @@ fn find_path(input: &Vec<Vec<char>>) -> Vec<(usize, usize, char)> {
         // );
         // we should build a list of directions without previous step
         let next_dirs = directions_from(direction);
-        let next_conns = connections(&input, next_row, next_col, &next_dirs);
+        let next_conns = connections(input, next_row, next_col, &next_dirs);
         // eprintln!("New connections: {:?}", next_conns);
         if next_conns.len() != 1 {
             panic!("We have more than 1 next connection!");
@@ -165,29 +167,22 @@ fn find_path(input: &Vec<Vec<char>>) -> Vec<(usize, usize, char)> {
 
 // Check available directions from the pipe reached by direction d.
 // So, if pipe were reached in northern direction, we should ignore south
-fn directions_from(d: Direction) -> Vec<Direction> {
-    let anti_d = match d {
-        Direction::North => Direction::South,
-        Direction::East => Direction::West,
-        Direction::South => Direction::North,
-        Direction::West => Direction::East,
-    };
-    all_directions()
-        .iter()
-        .filter(|d| **d != anti_d)
-        .cloned()
+pub fn directions_from(d: Direction) -> Vec<Direction> {
+    Direction::ALL
+        .into_iter()
+        .filter(|other| *other != d.reverse())
         .collect()
 }
 
 // Get all possible steps from given position in given directions
-fn connections(
-    field: &Vec<Vec<char>>,
+pub fn connections(
+    field: &Grid<char>,
     row: usize,
     col: usize,
     directions: &[Direction],
 ) -> HashMap<Direction, char> {
-    if field[row][col] == '.' {
-        eprintln!("Unexpected: search for direction from '.'");
+    if field[(row, col)] == '.' {
+        trace!("Unexpected: search for direction from '.'");
         return HashMap::new();
     }
     // let's restart this Idea again
@@ -201,14 +196,14 @@ fn connections(
         ('J', vec![Direction::West, Direction::North]),
         ('7', vec![Direction::West, Direction::South]),
         ('F', vec![Direction::South, Direction::East]),
-        ('S', all_directions()),
+        ('S', Direction::ALL.to_vec()),
     ]);
-    let curr_pipe = field[row][col];
+    let curr_pipe = field[(row, col)];
     let possible_directions: Vec<(Direction, char)> = pipe_directions[&curr_pipe]
         .iter()
         .filter(|d| directions.contains(d))
         // we peek in each direction and collect positive values with related directions
-        .filter_map(|d| peek(&field, row, col, *d).map(|c| (*d, c)))
+        .filter_map(|d| peek(field, row, col, *d).map(|c| (*d, c)))
         // TODO: remove this hack in separate step
         .filter(|(_, c)| *c != '.')
         .collect();
@@ -234,113 +229,150 @@ fn connections(
     for (d, c) in actual_connections {
         result.insert(d, c);
     }
-    return result;
+    result
 }
 
 // Return an adjacent character to the given position according to direction
-fn peek(field: &Vec<Vec<char>>, row: usize, col: usize, direction: Direction) -> Option<char> {
-    let step = try_step(field.len(), field[0].len(), row, col, direction);
-    step.map(|(new_row, new_col)| field[new_row][new_col])
+pub fn peek(field: &Grid<char>, row: usize, col: usize, direction: Direction) -> Option<char> {
+    Point::new(row, col)
+        .step(direction, field.bounds())
+        .map(|p| field[p])
 }
 
-fn try_step(
-    max_rows: usize,
-    max_cols: usize,
-    row: usize,
-    col: usize,
-    direction: Direction,
-) -> Option<(usize, usize)> {
-    if direction == Direction::North && row == 0 {
-        return None;
-    }
-    if direction == Direction::East && col == max_cols - 1 {
-        return None;
+// `size` x `size` field, at least 3 x 3, with a single loop among junk pipes.
+// The loop is the outline of a random shape made of columns of tiles: every column
+// overlaps the next one, so the outline never touches itself.
+pub fn generate(rng: &mut Rng, size: usize) -> Vec<String> {
+    let size = size.max(3);
+    // the shape is made of cells between the tiles, its corners are tiles
+    let cells = size - 1;
+    let first = rng.below(cells.div_ceil(3));
+    let last = rng.range((cells * 2 / 3) as u64..cells as u64) as usize;
+    let mut tops = vec![rng.below(cells)];
+    let mut bottoms = vec![rng.range(tops[0] as u64..cells as u64) as usize];
+    for _ in first..last {
+        let (top, bottom) = (tops[tops.len() - 1], bottoms[bottoms.len() - 1]);
+        let next_top = rng.range(top.saturating_sub(2) as u64..(top + 2).min(bottom) as u64 + 1);
+        let low = top.max(next_top as usize).max(bottom.saturating_sub(2));
+        let next_bottom = rng.range(low as u64..(bottom + 2).min(cells - 1) as u64 + 1);
+        tops.push(next_top as usize);
+        bottoms.push(next_bottom as usize);
     }
-    if direction == Direction::South && row == max_rows - 1 {
-        return None;
-    }
-    if direction == Direction::West && col == 0 {
-        return None;
-    }
-    match direction {
-        Direction::North => {
-            if row == 0 {
-                None
-            } else {
-                Some((row - 1, col))
-            }
-        }
-        Direction::East => {
-            if col == max_cols - 1 {
-                None
+    // walk the outline clockwise from the top left corner
+    let mut path = vec![(tops[0], first)];
+    let walk_to = |path: &mut Vec<(usize, usize)>, to: (usize, usize)| {
+        while path[path.len() - 1] != to {
+            let (row, col) = path[path.len() - 1];
+            let step = |from: usize, to: usize| if to > from { from + 1 } else { from - 1 };
+            if row != to.0 {
+                path.push((step(row, to.0), col));
             } else {
-                Some((row, col + 1))
+                path.push((row, step(col, to.1)));
             }
         }
-        Direction::South => {
-            if row == max_rows - 1 {
-                None
-            } else {
-                Some((row + 1, col))
-            }
-        }
-        Direction::West => {
-            if col == 0 {
-                None
-            } else {
-                Some((row, col - 1))
-            }
+    };
+    for (idx, col) in (first..=last).enumerate() {
+        walk_to(&mut path, (tops[idx], col));
+        walk_to(&mut path, (tops[idx], col + 1));
+    }
+    for (idx, col) in (first..last + 1).enumerate().rev() {
+        walk_to(&mut path, (bottoms[idx] + 1, col + 1));
+        walk_to(&mut path, (bottoms[idx] + 1, col));
+    }
+    walk_to(&mut path, (tops[0], first));
+    path.pop();
+
+    let mut field = Grid::new(size, size, '.');
+    for pos in field.positions().collect::<Vec<_>>() {
+        if !rng.chance(0.5) {
+            field[pos] = *rng.choose(&['|', '-', 'L', 'J', '7', 'F']);
         }
     }
+    for (idx, &(row, col)) in path.iter().enumerate() {
+        let prev = path[(idx + path.len() - 1) % path.len()];
+        let next = path[(idx + 1) % path.len()];
+        let side = |(r, c): (usize, usize)| match (r.cmp(&row), c.cmp(&col)) {
+            (Ordering::Less, _) => 'N',
+            (Ordering::Greater, _) => 'S',
+            (_, Ordering::Less) => 'W',
+            _ => 'E',
+        };
+        let mut sides = [side(prev), side(next)];
+        sides.sort();
+        field[(row, col)] = match sides {
+            ['N', 'S'] => '|',
+            ['E', 'W'] => '-',
+            ['E', 'N'] => 'L',
+            ['N', 'W'] => 'J',
+            ['S', 'W'] => '7',
+            _ => 'F',
+        };
+    }
+    field[*rng.choose(&path)] = 'S';
+    field.rows().map(String::from_iter).collect()
 }
 
-/**
- * The enum parameters are deltas by
- */
-#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
-enum Direction {
-    North,
-    East,
-    South,
-    West,
+pub fn check(input_path: &Path) -> Result<Vec<ParseError>> {
+    let lines = input::load(input_path, input::read_lines)?;
+    Ok(problems(&lines))
 }
 
-fn delta(d: Direction) -> (i16, i16) {
-    match d {
-        Direction::North => (-1, 0),
-        Direction::East => (0, 1),
-        Direction::South => (1, 0),
-        Direction::West => (0, -1),
-    }
+// Load 2d slice
+pub fn load_input(input_path: &Path) -> Result<Grid<char>> {
+    input::load(input_path, parse_input)
 }
 
-fn all_directions() -> Vec<Direction> {
-    vec![
-        Direction::North,
-        Direction::East,
-        Direction::South,
-        Direction::West,
-    ]
+pub fn parse_input(reader: impl BufRead) -> Result<Grid<char>> {
+    let lines = input::read_lines(reader)?;
+    if let Some(e) = problems(&lines).into_iter().next() {
+        return Err(e.into());
+    }
+    Ok(Grid::from_rows(
+        lines.iter().map(|line| line.chars().collect()).collect(),
+    ))
 }
 
-// Load 2d slice
-fn load_input(input_path: &Path) -> io::Result<Vec<Vec<char>>> {
-    let file = File::open(input_path)?;
-    let buf_reader = io::BufReader::new(file);
-    let lines = buf_reader.lines();
-    lines
-        .map(|line| line.map(|l| Vec::from_iter(l.chars())))
-        .collect()
+// Problems of the field: unknown tiles, ragged rows and other than exactly one start
+fn problems(lines: &[String]) -> Vec<ParseError> {
+    let mut result = input::grid_problems(lines, "|-LJ7F.S");
+    let starts: Vec<(usize, usize)> = lines
+        .iter()
+        .enumerate()
+        .flat_map(|(row, line)| line.match_indices('S').map(move |(col, _)| (row, col)))
+        .collect();
+    for &(row, col) in starts.iter().skip(1) {
+        let line = &lines[row];
+        result.push(ParseError::new(line, &line[col..col + 1], "only one start").on_line(row + 1));
+    }
+    if starts.is_empty() {
+        let line = lines.last().map(|l| l.as_str()).unwrap_or("");
+        result.push(ParseError::at_end(line, "start 'S' in the field").on_line(lines.len().max(1)));
+    }
+    result.sort_by_key(|e| (e.line, e.column));
+    result
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::path::Path;
+
+    const SIMPLE_LOOP: &str = "\
+-L|F7
+7S-7|
+L|7||
+-L-J|
+L|-JF";
+
+    const COMPLEX_LOOP: &str = "\
+7-F7-
+.FJ|7
+SJLL7
+|F--J
+LJ.LJ";
 
     #[test]
     fn peek_is_correct() {
-        let input = load_input(Path::new("day102.test")).unwrap();
+        let input = parse_input(COMPLEX_LOOP.as_bytes()).unwrap();
         // We know that S resides at (2, 0)
         assert_eq!(Some('.'), peek(&input, 2, 0, Direction::North));
         assert_eq!(Some('J'), peek(&input, 2, 0, Direction::East));
@@ -350,22 +382,22 @@ mod tests {
 
     #[test]
     fn test_no_possible_directions() {
-        let input = load_input(Path::new("day102.test")).unwrap();
-        let result = connections(&input, 0, 0, &all_directions());
-        eprintln!("{:?}", &result);
+        let input = parse_input(COMPLEX_LOOP.as_bytes()).unwrap();
+        let result = connections(&input, 0, 0, &Direction::ALL);
+        debug!("{:?}", &result);
         assert!(result.is_empty());
     }
 
     #[test]
     fn test_good_directions() {
-        let input = load_input(Path::new("day102.test")).unwrap();
-        let result1 = connections(&input, 2, 0, &all_directions());
+        let input = parse_input(COMPLEX_LOOP.as_bytes()).unwrap();
+        let result1 = connections(&input, 2, 0, &Direction::ALL);
         assert_eq!(2, result1.len());
         assert!(result1.contains_key(&Direction::East));
         assert!(result1.contains_key(&Direction::South));
         assert_eq!('J', result1[&Direction::East]);
         assert_eq!('|', result1[&Direction::South]);
-        let result2 = connections(&input, 3, 2, &all_directions());
+        let result2 = connections(&input, 3, 2, &Direction::ALL);
         assert_eq!(2, result2.len());
         assert!(result2.contains_key(&Direction::East));
         assert!(result2.contains_key(&Direction::West));
@@ -375,8 +407,8 @@ mod tests {
 
     #[test]
     fn test_compatible_connections() {
-        let input = load_input(Path::new("day101.test")).unwrap();
-        let result = connections(&input, 1, 1, &all_directions());
+        let input = parse_input(SIMPLE_LOOP.as_bytes()).unwrap();
+        let result = connections(&input, 1, 1, &Direction::ALL);
         // we need to make sure that only compatible pipes are returned
         assert_eq!(2, result.len());
         assert!(result.contains_key(&Direction::East));
@@ -393,4 +425,17 @@ mod tests {
         assert!(result.contains(&Direction::North));
         assert!(result.contains(&Direction::West));
     }
+
+    #[test]
+    fn test_problems() {
+        let lines: Vec<String> = SIMPLE_LOOP.lines().map(|l| l.to_string()).collect();
+        assert!(problems(&lines).is_empty());
+        let no_start: Vec<String> = lines.iter().map(|l| l.replace('S', "F")).collect();
+        assert_eq!("start 'S' in the field", problems(&no_start)[0].expected);
+        let mut two_starts = lines.clone();
+        two_starts[4] = "L|-JS".to_string();
+        let problems = problems(&two_starts);
+        assert_eq!(1, problems.len());
+        assert_eq!((5, 5), (problems[0].line, problems[0].column));
+    }
 }
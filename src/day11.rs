@@ -1,53 +1,55 @@
-use std::fs::File;
-use std::io::{self, BufRead};
+use crate::answer::Answer;
+use crate::error::{ParseError, Result};
+use crate::grid::Grid;
+use crate::input;
+use crate::rng::Rng;
+use crate::{debug, trace};
+use std::io::BufRead;
 use std::path::Path;
 
-pub fn part1(input_path: &Path) {
-    let image = load_input(input_path).unwrap();
+pub fn part1(input_path: &Path) -> Result<Answer> {
+    let image = load_input(input_path)?;
     let result = calc_expanded_paths(&image, 2);
-    println!("Result: {}", result);
+    Ok(result.into())
 }
 
-pub fn part2(input_path: &Path) {
-    let image = load_input(input_path).unwrap();
+pub fn part2(input_path: &Path) -> Result<Answer> {
+    let image = load_input(input_path)?;
     let result = calc_expanded_paths(&image, 1_000_000);
-    println!("Result: {}", result);
+    Ok(result.into())
 }
 
-fn calc_expanded_paths(image: &Vec<Vec<char>>, expansion_scale: u32) -> u64 {
-    eprintln!("Input image:");
-    eprintln_image(&image);
+pub fn calc_expanded_paths(image: &Grid<char>, expansion_scale: u32) -> u64 {
+    debug!("Input image:\n{}", image);
     let galaxy_coords: Vec<(u32, u32)> = image
         .iter()
-        .enumerate()
-        .flat_map(|(i, row)| {
-            row.iter()
-                .enumerate()
-                .filter(|(_, c)| **c == '#')
-                .map(move |(j, _)| (i as u32, j as u32))
-        })
+        .filter(|(_, c)| **c == '#')
+        .map(|((i, j), _)| (i as u32, j as u32))
         .collect();
-    eprintln!("Original galaxy coords: {:?}", &galaxy_coords);
+    debug!("Original galaxy coords: {:?}", &galaxy_coords);
     // now we find empty rows and cols
-    let empty_rows: Vec<u32> = empty_rows(&image);
-    let empty_cols: Vec<u32> = empty_cols(&image);
+    let empty_rows: Vec<u32> = empty_rows(image);
+    let empty_cols: Vec<u32> = empty_cols(image);
     let expanded_galaxy_coords =
         expand_coords(&galaxy_coords, &empty_rows, &empty_cols, expansion_scale);
-    eprintln!("Expanded galaxy coords: {:?}", &expanded_galaxy_coords);
+    debug!("Expanded galaxy coords: {:?}", &expanded_galaxy_coords);
 
     // now we have to take all # pairs and calcualate the distance between them
     let mut distances: Vec<u32> = Vec::new();
 
     let mut count = 0;
-    for i in 0..expanded_galaxy_coords.len() - 1 {
+    for i in 0..expanded_galaxy_coords.len().saturating_sub(1) {
         for j in (i + 1)..expanded_galaxy_coords.len() {
             count += 1;
             let g1 = expanded_galaxy_coords[i];
             let g2 = expanded_galaxy_coords[j];
             let dist = distance(g1, g2);
-            eprintln!(
+            trace!(
                 "{:02}: distance between {:?} and {:?} = {}",
-                count, g1, g2, dist
+                count,
+                g1,
+                g2,
+                dist
             );
             distances.push(dist);
         }
@@ -56,35 +58,32 @@ fn calc_expanded_paths(image: &Vec<Vec<char>>, expansion_scale: u32) -> u64 {
     distances.iter().map(|x| *x as u64).sum()
 }
 
-fn distance(g1: (u32, u32), g2: (u32, u32)) -> u32 {
+pub fn distance(g1: (u32, u32), g2: (u32, u32)) -> u32 {
     let result = (g1.0 as i32 - g2.0 as i32).abs() + (g1.1 as i32 - g2.1 as i32).abs();
     result as u32
 }
 
-fn empty_rows(image: &Vec<Vec<char>>) -> Vec<u32> {
+pub fn empty_rows(image: &Grid<char>) -> Vec<u32> {
     image
-        .iter()
+        .rows()
         .enumerate()
         .filter(|(_, row)| row.iter().all(|c| *c == '.'))
         .map(|(i, _)| i as u32)
         .collect()
 }
 
-fn empty_cols(image: &Vec<Vec<char>>) -> Vec<u32> {
-    // TODO: iterators
-    let mut empty_col_idxs: Vec<u32> = Vec::new();
-    for j in 0..image[0].len() {
-        if image.iter().all(|r| r[j] == '.') {
-            empty_col_idxs.push(j as u32);
-        }
-    }
-    empty_col_idxs
+pub fn empty_cols(image: &Grid<char>) -> Vec<u32> {
+    image
+        .columns()
+        .enumerate()
+        .filter_map(|(j, mut col)| col.all(|c| *c == '.').then_some(j as u32))
+        .collect()
 }
 
-fn expand_coords(
-    galaxy_coords: &Vec<(u32, u32)>,
-    empty_rows: &Vec<u32>,
-    empty_cols: &Vec<u32>,
+pub fn expand_coords(
+    galaxy_coords: &[(u32, u32)],
+    empty_rows: &[u32],
+    empty_cols: &[u32],
     expansion_scale: u32,
 ) -> Vec<(u32, u32)> {
     // each coord's i (and j) is increased by number of empty rows (or cols) before this galaxy's coord multiplied by scale
@@ -101,20 +100,36 @@ fn expand_coords(
         .collect()
 }
 
-fn eprintln_image(image: &Vec<Vec<char>>) {
-    for row in image {
-        for c in row {
-            eprint!("{}", c);
-        }
-        eprintln!()
+// `size` x `size` image, at least 2 x 2, with a galaxy on about every 20th pixel.
+// There are always two galaxies or more, so there is a pair to measure.
+pub fn generate(rng: &mut Rng, size: usize) -> Vec<String> {
+    let size = size.max(2);
+    let mut image: Vec<Vec<char>> = (0..size)
+        .map(|_| {
+            (0..size)
+                .map(|_| if rng.chance(0.05) { '#' } else { '.' })
+                .collect()
+        })
+        .collect();
+    // one galaxy in each half of the pixels, so they never land on the same one
+    let half = size * size / 2;
+    for pixel in [rng.below(half), half + rng.below(size * size - half)] {
+        image[pixel / size][pixel % size] = '#';
     }
+    image
+        .into_iter()
+        .map(|row| row.into_iter().collect())
+        .collect()
 }
 
-fn load_input(input_path: &Path) -> io::Result<Vec<Vec<char>>> {
-    let file = File::open(input_path)?;
-    let buf_reader = io::BufReader::new(file);
-    buf_reader
-        .lines()
-        .map(|line| line.map(|l| Vec::from_iter(l.chars())))
-        .collect()
+pub fn check(input_path: &Path) -> Result<Vec<ParseError>> {
+    input::check_grid(input_path, ".#")
+}
+
+pub fn load_input(input_path: &Path) -> Result<Grid<char>> {
+    input::load(input_path, parse_input)
+}
+
+pub fn parse_input(reader: impl BufRead) -> Result<Grid<char>> {
+    input::parse_grid(reader, ".#")
 }
@@ -1,31 +1,35 @@
+use crate::answer::Answer;
+use crate::error::{parse_num, ParseError, Result};
+use crate::input;
+use crate::rng::Rng;
+use crate::{debug, trace};
 use std::fmt::Display;
-use std::fs::File;
-use std::io::{self, BufRead};
-use std::iter::repeat;
+use std::io::BufRead;
 use std::path::Path;
 
-pub fn part1(input_path: &Path) {
-    let input = load_input(input_path).unwrap();
-    eprintln!("Loaded input:");
+// Unfinished: only the first two records are looked at, and each counts as one arrangement
+pub fn part1(input_path: &Path) -> Result<Answer> {
+    let input = load_input(input_path)?;
+    debug!("Loaded input:");
     for c in &input {
-        eprintln!("{}", c);
+        debug!("{}", c);
     }
     let result: u32 = input.iter().take(2).map(count_arrangements).sum();
-    println!("Result: {}", result)
+    Ok(result.into())
 }
 
 #[allow(unused)]
-pub fn part2(input_path: &Path) {
+pub fn part2(input_path: &Path) -> Result<Answer> {
     todo!()
 }
 
-fn count_arrangements(cond: &Condition) -> u32 {
-    eprintln!("Arrangements for:\n'{}'", cond);
+pub fn count_arrangements(cond: &Condition) -> u32 {
+    trace!("Arrangements for:\n'{}'", cond);
     // let mut test_arrangement = Vec::new();
     let group_strs: Vec<String> = cond
         .damaged_groups
         .iter()
-        .map(|size| repeat('#').take(*size as usize).collect())
+        .map(|size| std::iter::repeat_n('#', *size as usize).collect())
         .collect();
 
     let mut first = true;
@@ -46,7 +50,7 @@ fn count_arrangements(cond: &Condition) -> u32 {
     for _ in 0..(result_string.capacity() - result_string.len()) {
         result_string.push('.');
     }
-    println!("'{}'", &result_string);
+    trace!("'{}'", &result_string);
 
     1
 }
@@ -55,8 +59,8 @@ fn count_arrangements(cond: &Condition) -> u32 {
 #[allow(dead_code)]
 fn all_positions(g: usize, cap: usize) -> Vec<String> {
     assert!(g <= cap);
-    let g_str = repeat('#').take(g).collect::<String>();
-    let mut result = Vec::with_capacity(cap as usize);
+    let g_str = std::iter::repeat_n('#', g).collect::<String>();
+    let mut result = Vec::with_capacity(cap);
     for i in 0..(cap - g + 1) {
         let mut new_pos = String::with_capacity(cap);
         for _ in 0..i {
@@ -73,12 +77,12 @@ fn all_positions(g: usize, cap: usize) -> Vec<String> {
 
 #[allow(dead_code)]
 fn format_groups(groups: &[u32]) -> String {
-    let mut group_str_iter = groups
+    let group_str_iter = groups
         .iter()
-        .map(|size| repeat('#').take(*size as usize).collect::<String>());
+        .map(|size| std::iter::repeat_n('#', *size as usize).collect::<String>());
     let mut first = true;
     let mut result = String::with_capacity(groups.len() * 2 - 1); // just a guess
-    while let Some(group) = group_str_iter.next() {
+    for group in group_str_iter {
         if first {
             first = false;
         } else {
@@ -89,25 +93,34 @@ fn format_groups(groups: &[u32]) -> String {
     result
 }
 
-struct Condition {
-    damaged_groups: Vec<u32>,
-    record: String,
+pub struct Condition {
+    pub damaged_groups: Vec<u32>,
+    pub record: String,
 }
 
 impl Condition {
-    fn from(cond_str: &str) -> Self {
-        let mut splits = cond_str.split_whitespace();
-        let record = splits.next().unwrap();
+    pub fn parse(line: &str) -> Result<Self, ParseError> {
+        let mut splits = line.split_whitespace();
+        let record = splits
+            .next()
+            .ok_or_else(|| ParseError::at_end(line, "record"))?;
+        if let Some((idx, c)) = record.char_indices().find(|(_, c)| !"?.#".contains(*c)) {
+            return Err(ParseError::new(
+                line,
+                &record[idx..idx + c.len_utf8()],
+                "one of '?.#'",
+            ));
+        }
         let groups: Vec<_> = splits
             .next()
-            .unwrap()
+            .ok_or_else(|| ParseError::at_end(line, "damaged groups"))?
             .split(',')
-            .map(|s| s.parse::<u32>().unwrap())
-            .collect();
-        Self {
+            .map(|s| parse_num::<u32>(line, s))
+            .collect::<Result<_, _>>()?;
+        Ok(Self {
             damaged_groups: groups,
             record: record.to_string(),
-        }
+        })
     }
 }
 
@@ -123,15 +136,42 @@ impl Display for Condition {
     }
 }
 
-fn load_input(input_path: &Path) -> io::Result<Vec<Condition>> {
-    let file = File::open(input_path)?;
-    let buf_reader = io::BufReader::new(file);
-    let lines = buf_reader.lines();
-    lines
-        .map(|line| line.map(|l| Condition::from(&l)))
+// `size` records of up to 20 springs, about a third of them unknown.
+// The damaged groups are taken from the springs before they are hidden.
+pub fn generate(rng: &mut Rng, size: usize) -> Vec<String> {
+    (0..size)
+        .map(|_| {
+            let len = rng.range(1..21) as usize;
+            let mut springs: Vec<char> = (0..len)
+                .map(|_| if rng.chance(0.5) { '#' } else { '.' })
+                .collect();
+            springs[rng.below(len)] = '#';
+            let groups: Vec<String> = springs
+                .split(|c| *c == '.')
+                .filter(|g| !g.is_empty())
+                .map(|g| g.len().to_string())
+                .collect();
+            let record: String = springs
+                .iter()
+                .map(|&c| if rng.chance(0.35) { '?' } else { c })
+                .collect();
+            format!("{} {}", record, groups.join(","))
+        })
         .collect()
 }
 
+pub fn check(input_path: &Path) -> Result<Vec<ParseError>> {
+    input::check_lines(input_path, Condition::parse)
+}
+
+pub fn load_input(input_path: &Path) -> Result<Vec<Condition>> {
+    input::load(input_path, parse_input)
+}
+
+pub fn parse_input(reader: impl BufRead) -> Result<Vec<Condition>> {
+    input::parse_lines(reader, Condition::parse)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -149,10 +189,19 @@ mod tests {
         assert_eq!(vec!["#...", ".#..", "..#.", "...#"], all_positions(1, 4))
     }
 
+    #[test]
+    fn test_parse_non_ascii() {
+        let e = Condition::parse("é?? 1").err().unwrap();
+        assert_eq!(
+            ("one of '?.#'", "'é'"),
+            (e.expected.as_str(), e.found.as_str())
+        );
+    }
+
     #[test]
     fn test_simple_arrangements() {
-        let cond = Condition::from("???.### 1,1,3");
-        eprintln!("Testing condition {}", cond);
+        let cond = Condition::parse("???.### 1,1,3").unwrap();
+        debug!("Testing condition {}", cond);
         assert_eq!(1, count_arrangements(&cond));
     }
 }
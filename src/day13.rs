@@ -1,59 +1,59 @@
+use crate::answer::Answer;
+use crate::error::{ParseError, Result};
+use crate::grid::Grid;
+use crate::input;
+use crate::rng::Rng;
+use crate::{debug, trace};
 use std::fmt::Display;
-use std::fs::File;
-use std::io::{self, BufRead};
+use std::io::BufRead;
 use std::iter::zip;
 use std::path::Path;
 
-pub fn part1(input_path: &Path) {
-    let input = load_input(input_path).unwrap();
-    let result: u32 = input
+pub fn part1(input_path: &Path) -> Result<Answer> {
+    let input = load_input(input_path)?;
+    let result = input
         .iter()
-        .map(|pat| {
-            eprintln!("Scan pattern:\n{}", pat);
-            find_horizontal_mirror(pat)
-                .map(|row| {
-                    eprintln!("Horizontal mirror at position {}", row + 1);
-                    ((row + 1) * 100) as u32
-                })
-                .or_else(|| {
-                    find_vertical_mirror(pat).map(|col| {
-                        eprintln!("Vertical mirror at position {}", col + 1);
-                        (col + 1) as u32
-                    })
-                })
-                .unwrap()
+        .map(|pat| summary(pat).map_err(|e| e.in_file(input_path)))
+        .sum::<Result<u32, _>>()?;
+    Ok(result.into())
+}
+
+// Rows above the horizontal mirror times 100, or else columns left of the vertical one.
+// A pattern without any mirror is an error about its first row.
+fn summary(pat: &Pattern) -> Result<u32, ParseError> {
+    debug!("Scan pattern:\n{}", pat);
+    find_horizontal_mirror(pat)
+        .map(|row| {
+            trace!("Horizontal mirror at position {}", row + 1);
+            ((row + 1) * 100) as u32
+        })
+        .or_else(|| {
+            find_vertical_mirror(pat).map(|col| {
+                trace!("Vertical mirror at position {}", col + 1);
+                (col + 1) as u32
+            })
+        })
+        .ok_or_else(|| {
+            let row: String = pat.data.row(0).iter().collect();
+            ParseError::new(&row, &row, "pattern with a mirror line").on_line(pat.first_line)
         })
-        .sum();
-    println!("Result: {}", result);
 }
 
 #[allow(unused)]
-pub fn part2(input_path: &Path) {
+pub fn part2(input_path: &Path) -> Result<Answer> {
     todo!()
 }
 
-fn find_vertical_mirror(pat: &Pattern) -> Option<usize> {
-    // find first possible position where two cols are equal
-    let width = pat.data[0].len();
-    let mirror_col_hyp = (0..width - 1).filter(|col| cols_eq(pat, *col, *col + 1));
-    for col in mirror_col_hyp {
-        // early exist for first/last columns
-        if col == 0 || col == width - 1 {
-            return Some(col);
-        }
-        // otherwise, let's check sideways
-        if zip((0..col).rev(), (col + 2)..width).all(|(col1, col2)| {
-            eprintln!("Compare columns {} - {}", col1, col2);
-            cols_eq(pat, col1, col2)
-        }) {
-            return Some(col);
-        }
-    }
-    None
+pub fn find_vertical_mirror(pat: &Pattern) -> Option<usize> {
+    // a vertical mirror is the horizontal one of the transposed pattern
+    find_horizontal_mirror(&Pattern {
+        data: pat.data.transpose(),
+        first_line: pat.first_line,
+    })
 }
 
-fn find_horizontal_mirror(pat: &Pattern) -> Option<usize> {
-    let height = pat.data.len();
+pub fn find_horizontal_mirror(pat: &Pattern) -> Option<usize> {
+    let height = pat.data.height();
     let mirror_row_hyp = (0..height - 1).filter(|row| rows_eq(pat, *row, *row + 1));
     for row in mirror_row_hyp {
         // early exist for first/last rows
@@ -62,7 +62,7 @@ fn find_horizontal_mirror(pat: &Pattern) -> Option<usize> {
         }
         // otherwise, let's check sideways
         if zip((0..row).rev(), (row + 2)..height).all(|(row1, row2)| {
-            eprintln!("Compare rows {} - {}", row1, row2);
+            trace!("Compare rows {} - {}", row1, row2);
             rows_eq(pat, row1, row2)
         }) {
             return Some(row);
@@ -71,55 +71,122 @@ fn find_horizontal_mirror(pat: &Pattern) -> Option<usize> {
     None
 }
 
-fn cols_eq(pat: &Pattern, col1: usize, col2: usize) -> bool {
-    let this_col_iter = pat.data.iter().map(|row| row[col1]);
-    let next_col_iter = pat.data.iter().map(|row| row[col2]);
-    this_col_iter.eq(next_col_iter)
-}
-
 fn rows_eq(pat: &Pattern, row1: usize, row2: usize) -> bool {
-    let this_row_iter = pat.data[row1].iter();
-    let next_row_iter = pat.data[row2].iter();
-    this_row_iter.eq(next_row_iter)
+    pat.data.row(row1) == pat.data.row(row2)
 }
 
-struct Pattern {
-    data: Vec<Vec<char>>,
+pub struct Pattern {
+    pub data: Grid<char>,
+    // 1-based number of the first row in the input
+    pub first_line: usize,
 }
 
 impl Display for Pattern {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "  ")?;
-        for j in 0..self.data[0].len() {
+        for j in 0..self.data.width() {
             write!(f, "{:X}", j)?;
         }
         writeln!(f)?;
-        for (i, row) in self.data.iter().enumerate() {
+        for (i, row) in self.data.rows().enumerate() {
             writeln!(f, "{:X} {}", i, row.iter().collect::<String>())?;
         }
         Ok(())
     }
 }
 
-type Input = Vec<Pattern>;
-
-fn load_input(input_path: &Path) -> io::Result<Input> {
-    let file = File::open(input_path)?;
-    let buf_reader = io::BufReader::new(file);
-    let mut lines = buf_reader.lines();
-    let mut result: Input = Vec::new();
-    let mut pattern: Vec<Vec<char>> = Vec::new();
-    while let Some(line_res) = lines.next() {
-        let line = line_res?;
-        if line.is_empty() {
-            result.push(Pattern { data: pattern });
-            pattern = Vec::new();
+pub type Input = Vec<Pattern>;
+
+// `size` patterns, each mirrored around a random vertical or horizontal line
+pub fn generate(rng: &mut Rng, size: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    for idx in 0..size {
+        if idx > 0 {
+            lines.push(String::new());
+        }
+        let (height, width) = (rng.range(5..18) as usize, rng.range(5..18) as usize);
+        let mut rows: Vec<Vec<char>> = (0..height)
+            .map(|_| {
+                (0..width)
+                    .map(|_| if rng.chance(0.5) { '#' } else { '.' })
+                    .collect()
+            })
+            .collect();
+        if rng.chance(0.5) {
+            // mirror between columns `mirror - 1` and `mirror`
+            let mirror = rng.range(1..width as u64) as usize;
+            for row in rows.iter_mut() {
+                for col in mirror..width.min(2 * mirror) {
+                    row[col] = row[2 * mirror - 1 - col];
+                }
+            }
         } else {
-            pattern.push(line.chars().collect());
+            let mirror = rng.range(1..height as u64) as usize;
+            for row in mirror..height.min(2 * mirror) {
+                rows[row] = rows[2 * mirror - 1 - row].clone();
+            }
         }
+        lines.extend(rows.into_iter().map(String::from_iter));
     }
-    if !pattern.is_empty() {
-        result.push(Pattern { data: pattern })
+    lines
+}
+
+pub fn check(input_path: &Path) -> Result<Vec<ParseError>> {
+    let lines = input::load(input_path, input::read_lines)?;
+    Ok(problems(&lines))
+}
+
+pub fn load_input(input_path: &Path) -> Result<Input> {
+    input::load(input_path, parse_input)
+}
+
+pub fn parse_input(reader: impl BufRead) -> Result<Input> {
+    let lines = input::read_lines(reader)?;
+    if let Some(e) = problems(&lines).into_iter().next() {
+        return Err(e.into());
+    }
+    let mut result = Vec::new();
+    // `start` is the index of the first line of the current pattern
+    let mut start = 0;
+    for pattern in lines.split(|line| line.is_empty()) {
+        if !pattern.is_empty() {
+            result.push(Pattern {
+                data: Grid::parse(pattern, ".#").unwrap(),
+                first_line: start + 1,
+            });
+        }
+        start += pattern.len() + 1;
     }
     Ok(result)
 }
+
+// Problems of every pattern: tiles other than '.' and '#' and rows of another width
+fn problems(lines: &[String]) -> Vec<ParseError> {
+    let mut result = Vec::new();
+    // `start` is the index of the first line of the current pattern
+    let mut start = 0;
+    for pattern in lines.split(|line| line.is_empty()) {
+        result.extend(input::grid_problems(pattern, ".#").into_iter().map(|e| {
+            let line = start + e.line;
+            e.on_line(line)
+        }));
+        start += pattern.len() + 1;
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pattern_without_mirror() {
+        let input = parse_input("#.##\n#.##\n\n#.\n.#\n".as_bytes()).unwrap();
+        assert_eq!(Ok(100), summary(&input[0]));
+        let e = summary(&input[1]).unwrap_err();
+        assert_eq!(
+            (4, "pattern with a mirror line", "'#.'"),
+            (e.line, e.expected.as_str(), e.found.as_str())
+        );
+    }
+}
@@ -1,63 +1,84 @@
-use std::fs::File;
-use std::io::{self, BufRead, Write};
+use crate::answer::Answer;
+use crate::cycle::Cycle;
+use crate::debug;
+use crate::error::{ParseError, Result};
+use crate::grid::Grid;
+use crate::input;
+use crate::rng::Rng;
+use std::io::BufRead;
+use std::io::Write;
 use std::path::Path;
 
-pub fn part1(input_path: &Path) {
-    let input = load_input(input_path).unwrap();
+pub fn part1(input_path: &Path) -> Result<Answer> {
+    let input = load_input(input_path)?;
     // println_panel(&mut stderr(), &input);
     let panel = slide_north(&input);
     // println!("After full tilt: ");
     // println_panel(&mut stderr(), &panel);
-    let result: usize = panel
-        .iter()
+    Ok(north_load(&panel).into())
+}
+
+pub fn north_load(panel: &Grid<char>) -> usize {
+    panel
+        .rows()
         .enumerate()
-        .map(|(row_idx, row)| row.iter().filter(|c| **c == 'O').count() * (panel.len() - row_idx))
-        .sum();
-    println!("Result: {}", result);
+        .map(|(row_idx, row)| {
+            row.iter().filter(|c| **c == 'O').count() * (panel.height() - row_idx)
+        })
+        .sum()
 }
 
 #[allow(unused)]
-fn println_panel(w: &mut dyn Write, input: &Vec<Vec<char>>) {
-    for row in input {
-        for c in row {
-            write!(w, "{}", c).unwrap();
-        }
-        writeln!(w).unwrap();
-    }
+fn println_panel(w: &mut dyn Write, input: &Grid<char>) {
+    writeln!(w, "{}", input).unwrap();
 }
 
-fn slide_north(panel: &Vec<Vec<char>>) -> Vec<Vec<char>> {
+pub fn slide_north(panel: &Grid<char>) -> Grid<char> {
+    slide_north_with(panel, slide_column_north)
+}
+
+pub fn slide_north_with(
+    panel: &Grid<char>,
+    slide_column: fn(&mut Grid<char>, usize),
+) -> Grid<char> {
     // we go column over column
     let mut result = panel.clone();
-    for j in 0..result[0].len() {
-        slide_column_north(&mut result, j);
+    for j in 0..result.width() {
+        slide_column(&mut result, j);
     }
     result
 }
 
-#[allow(unused)]
-fn slide_column_north2(panel: &mut Vec<Vec<char>>, col: usize) {
+pub fn slide_column_north2(panel: &mut Grid<char>, col: usize) {
     // another approach:
-    //   find all possible 'stopping' locations (top, first O in column, #)
+    //   find all possible 'stopping' locations (top, #)
     //   count the number of O between stopping locations
     //   write down number of Os after each stopping location
-    // top is always a 'stopping' position
-    let mut stop_cols = vec![0];
-    // if column starts with '.' - its a stopping position, otherwise we look for first 'O'
-    if panel[0][col] == '.' {
-        stop_cols.push(0);
+    // top is always a 'stopping' position, `stop` is the first row after it
+    let mut stop = 0;
+    let mut rocks = 0;
+    for row in 0..=panel.height() {
+        if row == panel.height() || panel[(row, col)] == '#' {
+            for (offset, stop_row) in (stop..row).enumerate() {
+                panel[(stop_row, col)] = if offset < rocks { 'O' } else { '.' };
+            }
+            stop = row + 1;
+            rocks = 0;
+        } else if panel[(row, col)] == 'O' {
+            rocks += 1;
+        }
     }
 }
 
-fn slide_column_north(panel: &mut Vec<Vec<char>>, col: usize) {
+pub fn slide_column_north(panel: &mut Grid<char>, col: usize) {
     // in column we scan from top to bottom
     // if current value is O => skip
     // if current value is # => skip
     // if current value is . => find next O and swap it here
     let mut cur_row = 0;
-    while cur_row < panel.len() {
+    while cur_row < panel.height() {
         // eprint!("row {}: ", cur_row);
-        let cur_char = panel[cur_row][col];
+        let cur_char = panel[(cur_row, col)];
         match cur_char {
             'O' | '#' => {
                 // eprintln!("char {}, skip", cur_char);
@@ -66,17 +87,19 @@ fn slide_column_north(panel: &mut Vec<Vec<char>>, col: usize) {
             '.' => {
                 // eprint!("char ., look for next O => ");
                 // take all forward rows and find 'O' char
-                let next_o = panel[cur_row + 1..]
-                    .iter()
+                let next_o = panel
+                    .column(col)
+                    .skip(cur_row + 1)
                     .enumerate()
-                    .find(|(_, c)| c[col] == 'O' || c[col] == '#');
+                    .find(|(_, c)| **c == 'O' || **c == '#')
+                    .map(|(delta, c)| (delta, *c));
                 // eprint!("{:?}", &next_o);
                 if let Some((delta, c)) = next_o {
                     let swap_row = cur_row + delta + 1;
-                    if c[col] == 'O' {
+                    if c == 'O' {
                         // eprintln!(" => swap rows {} - {}", cur_row, swap_row);
-                        panel[cur_row][col] = 'O';
-                        panel[swap_row][col] = '.';
+                        panel[(cur_row, col)] = 'O';
+                        panel[(swap_row, col)] = '.';
                         cur_row += 1;
                     } else {
                         // if we encounter '#' we may continue from it
@@ -93,40 +116,117 @@ fn slide_column_north(panel: &mut Vec<Vec<char>>, col: usize) {
     }
 }
 
-#[allow(unused)]
-pub fn part2(input_path: &Path) {
-    todo!()
+pub fn part2(input_path: &Path) -> Result<Answer> {
+    let input = load_input(input_path)?;
+    // the rocks settle into a loop long before the billionth spin cycle
+    let cycle = Cycle::find(input, spin_cycle);
+    debug!(
+        "Spin cycles loop every {} from cycle {}",
+        cycle.period, cycle.start
+    );
+    Ok(north_load(cycle.state_at(1_000_000_000)).into())
 }
 
-fn load_input(input_path: &Path) -> io::Result<Vec<Vec<char>>> {
-    let file = File::open(input_path)?;
-    let buf_reader = io::BufReader::new(file);
-    buf_reader
-        .lines()
-        .map(|line| line.map(|l| l.chars().collect()))
+// Tilt north, west, south and east. Turning the platform clockwise brings
+// the next direction up, and four turns bring it back where it was.
+pub fn spin_cycle(panel: &Grid<char>) -> Grid<char> {
+    (0..4).fold(panel.clone(), |panel, _| {
+        slide_north(&panel).rotate_clockwise()
+    })
+}
+
+// `size` x `size` platform with rounded rocks on every 5th tile and cube rocks on every 10th
+pub fn generate(rng: &mut Rng, size: usize) -> Vec<String> {
+    (0..size)
+        .map(|_| {
+            (0..size)
+                .map(|_| match rng.below(10) {
+                    0 | 1 => 'O',
+                    2 => '#',
+                    _ => '.',
+                })
+                .collect()
+        })
         .collect()
 }
 
+pub fn check(input_path: &Path) -> Result<Vec<ParseError>> {
+    input::check_grid(input_path, ".#O")
+}
+
+pub fn load_input(input_path: &Path) -> Result<Grid<char>> {
+    input::load(input_path, parse_input)
+}
+
+pub fn parse_input(reader: impl BufRead) -> Result<Grid<char>> {
+    input::parse_grid(reader, ".#O")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    const EXAMPLE: &str = "\
+O....#....
+O.OO#....#
+.....##...
+OO.#O....O
+.O.....O#.
+O.#..O.#.#
+..O..#O..O
+.......O..
+#....###..
+#OO..#....";
+
     #[test]
     fn test_slide_north() {
-        let input = load_input(Path::new("day141.test")).unwrap();
+        let input = parse_input(EXAMPLE.as_bytes()).unwrap();
         let result = slide_north(&input);
-        let expected = vec![
-            vec!['O', 'O', 'O', 'O', '.', '#', '.', 'O', '.', '.'],
-            vec!['O', 'O', '.', '.', '#', '.', '.', '.', '.', '#'],
-            vec!['O', 'O', '.', '.', 'O', '#', '#', '.', '.', 'O'],
-            vec!['O', '.', '.', '#', '.', 'O', 'O', '.', '.', '.'],
-            vec!['.', '.', '.', '.', '.', '.', '.', '.', '#', '.'],
-            vec!['.', '.', '#', '.', '.', '.', '.', '#', '.', '#'],
-            vec!['.', '.', 'O', '.', '.', '#', '.', 'O', '.', 'O'],
-            vec!['.', '.', 'O', '.', '.', '.', '.', '.', '.', '.'],
-            vec!['#', '.', '.', '.', '.', '#', '#', '#', '.', '.'],
-            vec!['#', '.', '.', '.', '.', '#', '.', '.', '.', '.'],
-        ];
-        assert_eq!(expected, result);
+        let expected = "\
+OOOO.#.O..
+OO..#....#
+OO..O##..O
+O..#.OO...
+........#.
+..#....#.#
+..O..#.O.O
+..O.......
+#....###..
+#....#....";
+        assert_eq!(expected, result.to_string());
+    }
+
+    // Part 1 sliding the rocks with slide_column_north2
+    fn part1_counting(input_path: &Path) -> Result<Answer> {
+        let input = load_input(input_path)?;
+        let panel = slide_north_with(&input, slide_column_north2);
+        Ok(north_load(&panel).into())
+    }
+
+    #[test]
+    fn test_slide_column_north2_agrees() {
+        let input = parse_input(EXAMPLE.as_bytes()).unwrap();
+        assert_eq!(
+            slide_north(&input),
+            slide_north_with(&input, slide_column_north2)
+        );
+        crate::diff::assert_agrees_with("day14", 1, part1_counting, 30, 12);
+    }
+
+    #[test]
+    fn test_spin_cycle() {
+        let input = parse_input(EXAMPLE.as_bytes()).unwrap();
+        let expected = "\
+.....#....
+....#...O#
+...OO##...
+.OO#......
+.....OOO#.
+.O#...O#.#
+....O#....
+......OOOO
+#...O###..
+#..OO#....";
+        assert_eq!(expected, spin_cycle(&input).to_string());
     }
 }
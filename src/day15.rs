@@ -1,18 +1,22 @@
-use std::fs::File;
-use std::io::{self, BufRead};
+use crate::answer::Answer;
+use crate::debug;
+use crate::error::{parse_num, ParseError, Result};
+use crate::input;
+use crate::rng::Rng;
+use std::io::BufRead;
 use std::path::Path;
 
-pub fn part1(input_path: &Path) {
-    let sequence = load_raw_input(input_path).unwrap();
+pub fn part1(input_path: &Path) -> Result<Answer> {
+    let sequence = load_raw_input(input_path)?;
     let result: u32 = sequence.split(",").map(hash).sum();
-    println!("Result: {}", result);
+    Ok(result.into())
 }
 
-pub fn part2(input_path: &Path) {
-    let input = load_raw_input(input_path).unwrap();
-    let seq = parse_input(&input);
-    eprintln!("Loaded op sequence:");
-    eprintln!("{:?}", seq);
+pub fn part2(input_path: &Path) -> Result<Answer> {
+    let input = load_raw_input(input_path)?;
+    let seq = parse_input(&input)?;
+    debug!("Loaded op sequence:");
+    debug!("{:?}", seq);
     let mut boxes: Vec<Vec<Lens>> = vec![vec![]; 256];
     for op in seq {
         match op {
@@ -35,10 +39,10 @@ pub fn part2(input_path: &Path) {
             }
         }
     }
-    eprintln!("Resulting boxes after sequence:");
+    debug!("Resulting boxes after sequence:");
     for (idx, boxx) in boxes.iter().enumerate() {
         if !boxx.is_empty() {
-            eprintln!("{:02} - {:?}", idx, boxx);
+            debug!("{:02} - {:?}", idx, boxx);
         }
     }
     let result: u32 = boxes
@@ -51,45 +55,100 @@ pub fn part2(input_path: &Path) {
                 .sum::<u32>()
         })
         .sum();
-    println!("Result: {}", result);
+    Ok(result.into())
 }
 
-fn hash(s: &str) -> u32 {
+pub fn hash(s: &str) -> u32 {
     s.chars().fold(0, |h, c| ((h + c as u32) * 17) % 256)
 }
 
 #[derive(Clone, Debug)]
-struct Lens<'a> {
-    label: &'a str,
-    focal_length: u32,
+pub struct Lens<'a> {
+    pub label: &'a str,
+    pub focal_length: u32,
 }
 
 #[derive(Debug)]
-enum Operation<'a> {
+pub enum Operation<'a> {
     // Add lens with given label and focal length
     Add(&'a str, u32),
     // Remove lens with given label
     Remove(&'a str),
 }
 
-fn parse_op(op_str: &str) -> Operation {
-    if let Some(pos) = op_str.chars().position(|c| c == '-') {
-        Operation::Remove(&op_str[0..pos])
-    } else if let Some(pos) = op_str.chars().position(|c| c == '=') {
-        Operation::Add(&op_str[0..pos], op_str[pos + 1..].parse().unwrap())
+// Parse single operation `op_str`, which is a slice of the whole `input` sequence
+pub fn parse_op<'a>(input: &str, op_str: &'a str) -> Result<Operation<'a>, ParseError> {
+    if let Some(pos) = op_str.find('-') {
+        Ok(Operation::Remove(&op_str[0..pos]))
+    } else if let Some(pos) = op_str.find('=') {
+        Ok(Operation::Add(
+            &op_str[0..pos],
+            parse_num(input, &op_str[pos + 1..])?,
+        ))
     } else {
-        panic!("Unexpected op string: '{}'", op_str)
+        Err(ParseError::new(input, op_str, "'-' or '=' operation"))
     }
 }
 
-fn parse_input(input: &str) -> Vec<Operation> {
-    input.split(",").map(parse_op).collect()
+pub fn parse_input(input: &str) -> Result<Vec<Operation<'_>>, ParseError> {
+    input
+        .split(",")
+        .map(|op_str| parse_op(input, op_str))
+        .collect::<Result<_, _>>()
+        .map_err(|e| e.on_line(1))
 }
 
-fn load_raw_input(input_path: &Path) -> io::Result<String> {
-    let file = File::open(input_path)?;
-    let buf_reader = io::BufReader::new(file);
-    buf_reader.lines().next().unwrap()
+// Initialization sequence of `size` steps. The labels come from a small pool,
+// so the steps keep changing the same lenses.
+pub fn generate(rng: &mut Rng, size: usize) -> Vec<String> {
+    let labels: Vec<String> = (0..(size / 4).max(1))
+        .map(|_| {
+            let len = rng.range(1..7) as usize;
+            rng.string("abcdefghijklmnopqrstuvwxyz", len)
+        })
+        .collect();
+    let steps: Vec<String> = (0..size)
+        .map(|_| {
+            let label = rng.choose(&labels);
+            if rng.chance(0.3) {
+                format!("{}-", label)
+            } else {
+                format!("{}={}", label, rng.range(1..10))
+            }
+        })
+        .collect();
+    vec![steps.join(",")]
+}
+
+// Problems of every step of the sequence
+pub fn check(input_path: &Path) -> Result<Vec<ParseError>> {
+    let lines = input::load(input_path, input::read_lines)?;
+    let line = match sequence_line(&lines) {
+        Ok(line) => line,
+        Err(e) => return Ok(vec![e]),
+    };
+    Ok(line
+        .split(",")
+        .filter_map(|op_str| parse_op(line, op_str).err())
+        .map(|e| e.on_line(1))
+        .collect())
+}
+
+pub fn load_raw_input(input_path: &Path) -> Result<String> {
+    input::load(input_path, parse_raw_input)
+}
+
+pub fn parse_raw_input(reader: impl BufRead) -> Result<String> {
+    let lines = input::read_lines(reader)?;
+    Ok(sequence_line(&lines)?.to_string())
+}
+
+// The whole initialization sequence is on the first line
+fn sequence_line(lines: &[String]) -> Result<&str, ParseError> {
+    lines
+        .first()
+        .map(|line| line.as_str())
+        .ok_or_else(|| ParseError::at_end("", "initialization sequence").on_line(1))
 }
 
 #[cfg(test)]
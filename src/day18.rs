@@ -1,66 +1,53 @@
+use crate::answer::Answer;
+use crate::error::{find, parse_num, ParseError, Result};
+use crate::geometry::Direction;
+use crate::grid::Grid;
+use crate::input;
+use crate::rng::Rng;
+use crate::{debug, trace};
 use std::collections::VecDeque;
 use std::fmt::Display;
-use std::fs::File;
-use std::io::{self, BufRead};
+use std::io::BufRead;
 use std::path::Path;
 
-pub fn part1(input_path: &Path) {
-    let dig_plan = load_input(input_path, parse_instruction_1).unwrap();
+pub fn part1(input_path: &Path) -> Result<Answer> {
+    let dig_plan = load_input(input_path, parse_instruction_1)?;
     let mut plan = draw_plan(&dig_plan);
     // now the naive approach is to build a 'picture' and then fill it horizontally
-    eprintln!("Result field");
-    for (i, row) in plan.iter().enumerate() {
-        print!("{:04}  ", i);
-        for c in row {
-            print!("{}", c);
-        }
-        println!();
-    }
+    debug!("Result field\n{}", plan_to_string(&plan));
     // now let's find first 'inner' position and try to fill from it
     let start_point = find_inner_point(&plan);
     let mut next_points = VecDeque::from([start_point]);
-    eprintln!("Found inside point: {:?}", start_point);
-    while !next_points.is_empty() {
-        let (i, j) = next_points.pop_front().unwrap();
-        plan[i][j] = '#';
+    debug!("Found inside point: {:?}", start_point);
+    while let Some(point) = next_points.pop_front() {
+        plan[point] = '#';
         // now let's try going in 4 directions
-        let directions = [(-1i32, 0i32), (0, 1), (1, 0), (0, -1)];
-        for (di, dj) in directions {
-            let new_i = (i as i32 + di) as usize;
-            let new_j = (j as i32 + dj) as usize;
-            if plan[new_i][new_j] == '.' {
-                plan[new_i][new_j] = '#';
-                next_points.push_back((new_i, new_j));
+        let neighbors: Vec<(usize, usize)> = plan.neighbors4(point).collect();
+        for next in neighbors {
+            if plan[next] == '.' {
+                plan[next] = '#';
+                next_points.push_back(next);
             }
         }
     }
-    eprintln!("Filled plan:");
-    for (i, row) in plan.iter().enumerate() {
-        print!("{:04}  ", i);
-        for c in row {
-            print!("{}", c);
-        }
-        println!();
-    }
-    let result: usize = plan
-        .iter()
-        .map(|row| row.iter().filter(|c| **c == '#').count())
-        .sum();
-    println!("Result: {}", result);
+    debug!("Filled plan:\n{}", plan_to_string(&plan));
+    let result = plan.count(|c| *c == '#');
+    Ok(result.into())
 }
 
-pub fn part2(input_path: &Path) {
-    let dig_plan = load_input(input_path, parse_instruction_2).unwrap();
-    eprintln!("Plan:");
+// Not registered yet: the sparse plan is drawn but the cells inside it are not counted
+pub fn part2(input_path: &Path) -> Result<Answer> {
+    let dig_plan = load_input(input_path, parse_instruction_2)?;
+    debug!("Plan:");
     for ds in &dig_plan {
-        eprintln!("{}", ds);
+        debug!("{}", ds);
     }
-    eprintln!("Estimage plan size as keys of keys");
+    debug!("Estimage plan size as keys of keys");
     let result: usize = dig_plan.iter().map(|ds| ds.meters).sum();
-    eprintln!("{} pixels map to {} bytes", result, result * 24);
+    debug!("{} pixels map to {} bytes", result, result * 24);
     let plan = draw_plan_sparse(&dig_plan);
     let total_elems: usize = plan.iter().map(|row| row.len()).sum();
-    eprintln!(
+    debug!(
         "Total loaded size: {} pixels, ~{} bytes",
         total_elems,
         total_elems * 16
@@ -110,20 +97,23 @@ pub fn part2(input_path: &Path) {
     //     .map(|row| row.iter().filter(|c| **c == '#').count())
     //     .sum();
     // println!("Result: {}", result);
+    todo!("count the cells inside the sparse plan")
 }
 
 // Take list of instructions and return drawn map
-fn draw_plan(instructions: &Vec<DigStep>) -> Vec<Vec<char>> {
+pub fn draw_plan(instructions: &[DigStep]) -> Grid<char> {
     let mut plan: Vec<Vec<char>> = vec![vec!['.']];
     let mut row_idx: usize = 0;
     let mut col_idx: usize = 0;
     for step in instructions {
-        eprintln!(
+        trace!(
             "Processing step: {}, row_idx={}, col_idx={}",
-            step, row_idx, col_idx
+            step,
+            row_idx,
+            col_idx
         );
         match step.dir {
-            Direction::Up => {
+            Direction::North => {
                 // we should insert (step.meters - row) rows up
                 let needed_rows = step.meters as i32 - row_idx as i32;
                 let needed_cols = plan[row_idx].len();
@@ -135,35 +125,35 @@ fn draw_plan(instructions: &Vec<DigStep>) -> Vec<Vec<char>> {
                     // for every inserted row our current position is shifted forward
                     row_idx += needed_rows as usize;
                 }
-                for i in (row_idx - step.meters)..row_idx {
-                    plan[i][col_idx] = '#';
+                for row in &mut plan[(row_idx - step.meters)..row_idx] {
+                    row[col_idx] = '#';
                 }
                 // row should move from current row meters back
-                row_idx = row_idx - step.meters;
+                row_idx -= step.meters;
             }
-            Direction::Down => {
+            Direction::South => {
                 // we should insert (step.meters - (len - row - 1)) rows down
                 let needed_rows = step.meters as i32 - (plan.len() - row_idx - 1) as i32;
                 let needed_cols = plan[row_idx].len();
                 if needed_rows > 0 {
-                    eprintln!("D: Reserving {} additional rows...", needed_rows);
+                    trace!("D: Reserving {} additional rows...", needed_rows);
                     plan.reserve(needed_rows as usize);
-                    eprintln!("D: Reserved. Adding row values...");
+                    trace!("D: Reserved. Adding row values...");
                     let new_row = vec!['.'; needed_cols];
                     for _ in 0..needed_rows {
                         plan.push(new_row.clone());
                     }
-                    eprintln!("D: Added");
+                    trace!("D: Added");
                 }
-                eprintln!("D: Filling down chars with '#':");
+                trace!("D: Filling down chars with '#':");
                 // insert '#' chars
-                for i in row_idx..(row_idx + step.meters + 1) {
-                    plan[i][col_idx] = '#';
+                for row in &mut plan[row_idx..(row_idx + step.meters + 1)] {
+                    row[col_idx] = '#';
                 }
-                eprintln!("D: Filled");
-                row_idx = row_idx + step.meters;
+                trace!("D: Filled");
+                row_idx += step.meters;
             }
-            Direction::Left => {
+            Direction::West => {
                 // we should insert (step.meters - col) cols left
                 let needed_cols = step.meters as i32 - col_idx as i32;
                 if needed_cols > 0 {
@@ -177,13 +167,11 @@ fn draw_plan(instructions: &Vec<DigStep>) -> Vec<Vec<char>> {
                     col_idx += needed_cols as usize;
                 }
                 // insert '#' chars
-                for j in (col_idx - step.meters)..col_idx {
-                    plan[row_idx][j] = '#';
-                }
+                plan[row_idx][(col_idx - step.meters)..col_idx].fill('#');
                 // col should move to the left
-                col_idx = col_idx - step.meters;
+                col_idx -= step.meters;
             }
-            Direction::Right => {
+            Direction::East => {
                 // we should insert (step.meters - (len - col - 1)) cols right
                 let needed_cols = step.meters as i32 - (plan[row_idx].len() - col_idx - 1) as i32;
                 if needed_cols > 0 {
@@ -195,30 +183,30 @@ fn draw_plan(instructions: &Vec<DigStep>) -> Vec<Vec<char>> {
                     }
                 }
                 // insert '#' chars
-                for j in col_idx..(col_idx + step.meters) {
-                    plan[row_idx][j] = '#';
-                }
-                col_idx = col_idx + step.meters;
+                plan[row_idx][col_idx..(col_idx + step.meters)].fill('#');
+                col_idx += step.meters;
             }
         }
     }
-    plan
+    Grid::from_rows(plan)
 }
 
 // Return list of lists: each row is represented by a list, each list contains (column, character)
-fn draw_plan_sparse(instructions: &Vec<DigStep>) -> Vec<Vec<(usize, char)>> {
+pub fn draw_plan_sparse(instructions: &[DigStep]) -> Vec<Vec<(usize, char)>> {
     let mut plan: Vec<Vec<(usize, char)>> = vec![vec![]];
     let mut row_idx: usize = 0;
     let mut col_idx: usize = 0;
     let mut max_height = 1;
     let mut max_width = 1;
     for step in instructions {
-        eprintln!(
+        trace!(
             "Processing step: {}, row_idx={}, col_idx={}",
-            step, row_idx, col_idx
+            step,
+            row_idx,
+            col_idx
         );
         match step.dir {
-            Direction::Up => {
+            Direction::North => {
                 // we should insert (step.meters - row) rows up
                 let needed_rows = step.meters as i32 - row_idx as i32;
                 if needed_rows > 0 {
@@ -232,13 +220,13 @@ fn draw_plan_sparse(instructions: &Vec<DigStep>) -> Vec<Vec<(usize, char)>> {
                     max_height += needed_rows as usize;
                 }
                 // now insert an element in every new row
-                for i in (row_idx - step.meters)..row_idx {
-                    plan[i].push((col_idx, '#'));
+                for row in &mut plan[(row_idx - step.meters)..row_idx] {
+                    row.push((col_idx, '#'));
                 }
                 // row should move from current row meters back
-                row_idx = row_idx - step.meters;
+                row_idx -= step.meters;
             }
-            Direction::Down => {
+            Direction::South => {
                 // we should insert (step.meters - (len - row - 1)) rows down
                 let needed_rows = step.meters as i32 - (max_height - row_idx - 1) as i32;
                 if needed_rows > 0 {
@@ -249,12 +237,12 @@ fn draw_plan_sparse(instructions: &Vec<DigStep>) -> Vec<Vec<(usize, char)>> {
                     max_height += needed_rows as usize;
                 }
                 // insert '#' chars
-                for i in row_idx..(row_idx + step.meters + 1) {
-                    plan[i].push((col_idx, '#'));
+                for row in &mut plan[row_idx..(row_idx + step.meters + 1)] {
+                    row.push((col_idx, '#'));
                 }
-                row_idx = row_idx + step.meters;
+                row_idx += step.meters;
             }
-            Direction::Left => {
+            Direction::West => {
                 // we should insert (step.meters - col) cols left
                 let needed_cols = step.meters as i32 - col_idx as i32;
                 if needed_cols > 0 {
@@ -273,9 +261,9 @@ fn draw_plan_sparse(instructions: &Vec<DigStep>) -> Vec<Vec<(usize, char)>> {
                     plan[row_idx].push((j, '#'));
                 }
                 // col should move to the left
-                col_idx = col_idx - step.meters;
+                col_idx -= step.meters;
             }
-            Direction::Right => {
+            Direction::East => {
                 // we should insert (step.meters - (len - col - 1)) cols right
                 let needed_cols = step.meters as i32 - (max_width - col_idx - 1) as i32;
                 if needed_cols > 0 {
@@ -291,7 +279,7 @@ fn draw_plan_sparse(instructions: &Vec<DigStep>) -> Vec<Vec<(usize, char)>> {
                 for j in col_idx..(col_idx + step.meters) {
                     plan[row_idx].push((j, '#'));
                 }
-                col_idx = col_idx + step.meters;
+                col_idx += step.meters;
             }
         }
     }
@@ -308,8 +296,18 @@ fn draw_plan_sparse(instructions: &Vec<DigStep>) -> Vec<Vec<(usize, char)>> {
     plan
 }
 
+// Plan rows prefixed with their numbers
+fn plan_to_string(plan: &Grid<char>) -> String {
+    plan.rows()
+        .enumerate()
+        .map(|(i, row)| format!("{:04}  {}", i, row.iter().collect::<String>()))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
 #[allow(dead_code)]
-fn print_plan_sparse(sparse_plan: &Vec<Vec<(usize, char)>>) {
+fn sparse_plan_to_string(sparse_plan: &[Vec<(usize, char)>]) -> String {
+    let mut result = String::new();
     // let max_width = sparse_plan
     //     .iter()
     //     .map(|row| row.last().map(|(col, _)| col).unwrap())
@@ -319,30 +317,31 @@ fn print_plan_sparse(sparse_plan: &Vec<Vec<(usize, char)>>) {
         let mut cur_col = 0;
         for (col, c) in row {
             if *col > cur_col {
-                print!("{}", ".".repeat(*col - cur_col));
+                result.push_str(&".".repeat(*col - cur_col));
             }
-            print!("{}", c);
+            result.push(*c);
             cur_col = col + 1;
         }
         // if cur_col < *max_width {
         //     print!("{}", ".".repeat(max_width - cur_col));
         // }
-        println!();
+        result.push('\n');
     }
+    result
 }
 
 // Find first point inside given plan
-fn find_inner_point(plan: &Vec<Vec<char>>) -> (usize, usize) {
+pub fn find_inner_point(plan: &Grid<char>) -> (usize, usize) {
     // to do that we find first elements with following pattern. The coordinates of x are the ones we looking for.
     // 11
     // 1x
-    for i in 0..plan.len() - 1 {
-        for j in 0..plan[i].len() - 1 {
+    for i in 0..plan.height() - 1 {
+        for j in 0..plan.width() - 1 {
             let window = [
-                plan[i][j],
-                plan[i][j + 1],
-                plan[i + 1][j],
-                plan[i + 1][j + 1],
+                plan[(i, j)],
+                plan[(i, j + 1)],
+                plan[(i + 1, j)],
+                plan[(i + 1, j + 1)],
             ];
             if window == ['#', '#', '#', '.'] {
                 return (i + 1, j + 1);
@@ -353,7 +352,7 @@ fn find_inner_point(plan: &Vec<Vec<char>>) -> (usize, usize) {
 }
 
 #[allow(dead_code)]
-fn scan_fill(plan: &Vec<Vec<char>>) {
+fn scan_fill(plan: &Grid<char>) {
     // now we scan each row from left to right
     // let's try scanning following symbols by mask of 1s:
     // 11
@@ -369,29 +368,28 @@ fn scan_fill(plan: &Vec<Vec<char>>) {
     //
     // 00     01
     // 01 ... 11
-    let mut target_plan = vec![vec!['.'; plan[0].len()]; plan.len()];
+    let mut target_plan = Grid::new(plan.width(), plan.height(), '.');
     // we scan row by row starting with second one
-    for row_idx in 1..plan.len() {
+    for row_idx in 1..plan.height() {
         let mut inside = false;
         let mut on_hor_line = false;
-        let row = &plan[row_idx];
-        for j in 1..row.len() {
+        for j in 1..plan.width() {
             let window = [
-                plan[row_idx - 1][j - 1],
-                plan[row_idx - 1][j],
-                plan[row_idx][j - 1],
-                plan[row_idx][j],
+                plan[(row_idx - 1, j - 1)],
+                plan[(row_idx - 1, j)],
+                plan[(row_idx, j - 1)],
+                plan[(row_idx, j)],
             ];
             match window {
                 ['#', '#', '.', _] => {
                     // we've crossed the line
                     inside = !inside;
-                    target_plan[row_idx][j - 1] = window[1];
+                    target_plan[(row_idx, j - 1)] = window[1];
                 }
                 [_, '#', '#', _] => {
                     // either top or bottom horizontal line
                     on_hor_line = true;
-                    target_plan[row_idx][j - 1] = window[1];
+                    target_plan[(row_idx, j - 1)] = window[1];
                 }
                 ['.', '#', '.', _] => {
                     if on_hor_line {
@@ -399,7 +397,7 @@ fn scan_fill(plan: &Vec<Vec<char>>) {
                     } else {
                         inside = !inside;
                     }
-                    target_plan[row_idx][j - 1] = window[1];
+                    target_plan[(row_idx, j - 1)] = window[1];
                     //     //
                     //     if on_hor_line {
                     //         // if we are finishing horizontal line - then we should switch 'inside' flag as well
@@ -407,108 +405,199 @@ fn scan_fill(plan: &Vec<Vec<char>>) {
                     //         inside = false;
                     //     }
                     //     if inside {
-                    //         target_plan[row_idx][j - 1] = '#'
+                    //         target_plan[(row_idx, j - 1)] = '#'
                     //     } else {
-                    //         target_plan[row_idx][j - 1] = window[1]
+                    //         target_plan[(row_idx, j - 1)] = window[1]
                     //     }
                 }
                 _ => {
                     if inside {
-                        target_plan[row_idx][j - 1] = '#'
+                        target_plan[(row_idx, j - 1)] = '#'
                     } else {
-                        target_plan[row_idx][j - 1] = window[1]
+                        target_plan[(row_idx, j - 1)] = window[1]
                     }
                 }
             }
         }
     }
-    eprintln!("Filled plan:");
-    for (i, row) in target_plan.iter().enumerate() {
-        print!("{:04}  ", i);
-        for c in row {
-            print!("{}", c);
-        }
-        println!();
-    }
-    let result: usize = target_plan
-        .iter()
-        .map(|row| row.iter().filter(|c| **c == '#').count())
-        .sum();
-    println!("Result: {}", result);
-}
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum Direction {
-    Up,
-    Down,
-    Left,
-    Right,
+    debug!("Filled plan:\n{}", plan_to_string(&target_plan));
+    let result = target_plan.count(|c| *c == '#');
+    debug!("Result: {}", result);
 }
 
 #[derive(Debug, PartialEq, Eq)]
-struct DigStep {
-    dir: Direction,
-    meters: usize,
+pub struct DigStep {
+    pub dir: Direction,
+    pub meters: usize,
 }
 
 impl Display for DigStep {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let dir_s = match self.dir {
-            Direction::Up => 'U',
-            Direction::Down => 'D',
-            Direction::Left => 'L',
-            Direction::Right => 'R',
-        };
-        write!(f, "{} {}", dir_s, self.meters)
+        write!(f, "{} {}", dir_letter(self.dir), self.meters)
     }
 }
 
-fn parse_dir(s: &str) -> Direction {
-    match s {
-        "U" => Direction::Up,
-        "D" => Direction::Down,
-        "L" => Direction::Left,
-        "R" => Direction::Right,
-        _ => panic!("Unexpected direction: {}", s),
+// Direction as it is written in the plan
+pub fn dir_letter(dir: Direction) -> char {
+    match dir {
+        Direction::North => 'U',
+        Direction::South => 'D',
+        Direction::West => 'L',
+        Direction::East => 'R',
     }
 }
 
-fn parse_instruction_1(line: &str) -> DigStep {
-    DigStep {
-        dir: parse_dir(&line[0..1]),
-        meters: line
-            .split_whitespace()
-            .nth(1)
-            .and_then(|s| s.parse().ok())
-            .unwrap(),
+// Parse direction `s`, which is a slice of `line`
+pub fn parse_dir(line: &str, s: &str) -> Result<Direction, ParseError> {
+    match s {
+        "U" => Ok(Direction::North),
+        "D" => Ok(Direction::South),
+        "L" => Ok(Direction::West),
+        "R" => Ok(Direction::East),
+        _ => Err(ParseError::new(line, s, "direction U, D, L or R")),
     }
 }
 
-fn parse_instruction_2(line: &str) -> DigStep {
-    let hex_s = &line[line.find('#').unwrap() + 1..(line.len() - 1)];
-    let dir_n: u8 = hex_s[5..6].parse().unwrap();
-    let dir = match dir_n {
-        0 => Direction::Right,
-        1 => Direction::Down,
-        2 => Direction::Left,
-        3 => Direction::Up,
-        _ => unimplemented!(),
+pub fn parse_instruction_1(line: &str) -> Result<DigStep, ParseError> {
+    let mut splits = line.split_whitespace();
+    let dir_s = splits
+        .next()
+        .ok_or_else(|| ParseError::at_end(line, "direction"))?;
+    let meters_s = splits
+        .next()
+        .ok_or_else(|| ParseError::at_end(line, "meters"))?;
+    Ok(DigStep {
+        dir: parse_dir(line, dir_s)?,
+        meters: parse_num(line, meters_s)?,
+    })
+}
+
+pub fn parse_instruction_2(line: &str) -> Result<DigStep, ParseError> {
+    // color is written as "(#70c710)": 5 hex digits of meters and 1 digit of direction
+    let hex_start = find(line, line, '#')? + 1;
+    let hex_s = match line.get(hex_start..hex_start + 6) {
+        Some(hex_s) if hex_s.chars().all(|c| c.is_ascii_hexdigit()) => hex_s,
+        _ => return Err(ParseError::new(line, &line[hex_start..], "6 hex digits")),
     };
-    DigStep {
-        dir: dir,
-        meters: usize::from_str_radix(&hex_s[0..5], 16).unwrap(),
+    if &line[hex_start + 6..] != ")" {
+        return Err(ParseError::new(line, &line[hex_start + 6..], "')'"));
     }
+    let dir = match &hex_s[5..6] {
+        "0" => Direction::East,
+        "1" => Direction::South,
+        "2" => Direction::West,
+        "3" => Direction::North,
+        dir_s => return Err(ParseError::new(line, dir_s, "direction digit 0-3")),
+    };
+    Ok(DigStep {
+        dir,
+        meters: usize::from_str_radix(&hex_s[0..5], 16).unwrap(),
+    })
 }
 
-fn load_input(input_path: &Path, parse_i: fn(&str) -> DigStep) -> io::Result<Vec<DigStep>> {
-    let file = File::open(input_path)?;
-    let buf_reader = io::BufReader::new(file);
-    buf_reader
-        .lines()
-        .map(|line| line.map(|l| parse_i(&l)))
+// Dig plan of 4 * `size` instructions. The lagoon of part 1 is small,
+// the one hidden in the colors for part 2 has another shape and is much larger.
+pub fn generate(rng: &mut Rng, size: usize) -> Vec<String> {
+    let size = size.max(1);
+    let small = lagoon_outline(rng, size, 10);
+    let large = lagoon_outline(rng, size, 100_000);
+    small
+        .iter()
+        .zip(&large)
+        .map(|((dir, meters), (color_dir, color_meters))| {
+            let color_dir = match color_dir {
+                Direction::East => 0,
+                Direction::South => 1,
+                Direction::West => 2,
+                Direction::North => 3,
+            };
+            format!(
+                "{} {} (#{:05x}{})",
+                dir_letter(*dir),
+                meters,
+                color_meters,
+                color_dir
+            )
+        })
         .collect()
 }
 
+// Outline of a lagoon made of `columns` columns of random widths and heights up to
+// a few times `scale`. Every column overlaps the next one and has another top and bottom,
+// so the outline never touches itself and has exactly 4 instructions per column.
+fn lagoon_outline(rng: &mut Rng, columns: usize, scale: i64) -> Vec<(Direction, i64)> {
+    let mut tops = vec![0];
+    let mut bottoms = vec![rng.range_i64(1..2 * scale + 1)];
+    for idx in 1..columns {
+        let (top, bottom) = (tops[idx - 1], bottoms[idx - 1]);
+        loop {
+            let next_top = top + rng.range_i64(-scale..scale + 1);
+            let next_bottom = bottom + rng.range_i64(-scale..scale + 1);
+            if next_top != top
+                && next_bottom != bottom
+                && next_top < bottom
+                && top < next_bottom
+                && next_top < next_bottom
+                && next_bottom - next_top <= 4 * scale
+            {
+                tops.push(next_top);
+                bottoms.push(next_bottom);
+                break;
+            }
+        }
+    }
+    let vertical = |from: i64, to: i64| {
+        if to > from {
+            (Direction::South, to - from)
+        } else {
+            (Direction::North, from - to)
+        }
+    };
+    let mut steps = Vec::with_capacity(4 * columns);
+    for idx in 0..columns {
+        if idx > 0 {
+            steps.push(vertical(tops[idx - 1], tops[idx]));
+        }
+        steps.push((Direction::East, rng.range_i64(1..scale + 1)));
+    }
+    steps.push((Direction::South, bottoms[columns - 1] - tops[columns - 1]));
+    // the bottom goes back with the same widths
+    let widths: Vec<i64> = steps
+        .iter()
+        .filter(|(dir, _)| matches!(dir, Direction::East))
+        .map(|(_, meters)| *meters)
+        .collect();
+    for idx in (0..columns).rev() {
+        steps.push((Direction::West, widths[idx]));
+        if idx > 0 {
+            steps.push(vertical(bottoms[idx], bottoms[idx - 1]));
+        }
+    }
+    steps.push((Direction::North, bottoms[0] - tops[0]));
+    steps
+}
+
+// Both parts read the same lines, the first by direction and meters, the second by color
+pub fn check(input_path: &Path) -> Result<Vec<ParseError>> {
+    input::check_lines(input_path, |line| {
+        parse_instruction_1(line).and_then(|_| parse_instruction_2(line))
+    })
+}
+
+pub fn load_input(
+    input_path: &Path,
+    parse_i: fn(&str) -> Result<DigStep, ParseError>,
+) -> Result<Vec<DigStep>> {
+    input::load(input_path, |reader| parse_input(reader, parse_i))
+}
+
+pub fn parse_input(
+    reader: impl BufRead,
+    parse_i: fn(&str) -> Result<DigStep, ParseError>,
+) -> Result<Vec<DigStep>> {
+    input::parse_lines(reader, parse_i)
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -516,17 +605,17 @@ mod test {
     #[test]
     fn test_parse_part_2() {
         assert_eq!(
-            DigStep {
-                dir: Direction::Right,
+            Ok(DigStep {
+                dir: Direction::East,
                 meters: 461937
-            },
+            }),
             parse_instruction_2("R 6 (#70c710)")
         );
         assert_eq!(
-            DigStep {
-                dir: Direction::Down,
+            Ok(DigStep {
+                dir: Direction::South,
                 meters: 56407
-            },
+            }),
             parse_instruction_2("D 5 (#0dc571)")
         );
     }
@@ -1,34 +1,39 @@
-use std::collections::HashMap;
-use std::fs::File;
-use std::io::{self, BufRead};
+use crate::answer::Answer;
+use crate::error::{find, parse_num, ParseError, Result};
+use crate::input;
+use crate::interval::IntervalSet;
+use crate::rng::Rng;
+use crate::{debug, trace};
+use std::collections::{HashMap, HashSet};
+use std::io::BufRead;
 use std::path::Path;
 
-pub fn part1(input_path: &Path) {
-    let Input { workflows, parts } = load_input(input_path).unwrap();
-    eprintln!("Workflows:");
-    for (_, w) in &workflows {
-        eprintln!("{:?}", w);
+pub fn part1(input_path: &Path) -> Result<Answer> {
+    let Input { workflows, parts } = load_input(input_path)?;
+    debug!("Workflows:");
+    for w in workflows.values() {
+        debug!("{:?}", w);
     }
-    eprintln!("Parts:");
+    debug!("Parts:");
     for p in &parts {
-        eprintln!("{:?}", p);
+        debug!("{:?}", p);
     }
     // let's run first part through workflows
     let mut accepted: Vec<&Part> = vec![];
     for p in &parts {
-        eprintln!("Checking part: {:?}", p);
+        trace!("Checking part: {:?}", p);
         let mut w = &workflows[&"in".to_string()];
-        eprintln!("Starting workflow: {:?}", w);
+        trace!("Starting workflow: {:?}", w);
         'w_loop: loop {
             'rule_loop: for r in &w.rules {
-                if let Some(wname) = match_rule(&p, &r) {
-                    eprintln!("Part matched for rule '{}'", wname);
+                if let Some(wname) = match_rule(p, r) {
+                    trace!("Part matched for rule '{}'", wname);
                     if wname == "A" {
-                        eprintln!("Accepted!");
-                        accepted.push(&p);
+                        trace!("Accepted!");
+                        accepted.push(p);
                         break 'w_loop;
                     } else if wname == "R" {
-                        eprintln!("Rejected!");
+                        trace!("Rejected!");
                         break 'w_loop;
                     } else {
                         w = &workflows[wname];
@@ -42,16 +47,64 @@ pub fn part1(input_path: &Path) {
         .iter()
         .map(|p| p.ratings.values().sum::<u32>())
         .sum();
-    println!("Result: {}", result);
+    Ok(result.into())
 }
 
-#[allow(unused)]
-pub fn part2(input_path: &Path) {
-    todo!()
+pub fn part2(input_path: &Path) -> Result<Answer> {
+    let Input { workflows, .. } = load_input(input_path)?;
+    // every category starts with all the ratings 1..=4000
+    let ratings: RatingSets = "xmas"
+        .chars()
+        .map(|c| (c, IntervalSet::from(1..4001)))
+        .collect();
+    Ok(accepted_count(&workflows, "in", ratings).into())
+}
+
+// Sets of the ratings a part may have in each category
+type RatingSets = HashMap<char, IntervalSet>;
+
+// Number of rating combinations from `ratings` accepted starting at workflow `wname`
+fn accepted_count(workflows: &HashMap<String, Workflow>, wname: &str, ratings: RatingSets) -> u64 {
+    match wname {
+        "A" => return ratings.values().map(|r| r.cardinality()).product(),
+        "R" => return 0,
+        _ => {}
+    }
+    trace!("Workflow {} gets {:?}", wname, &ratings);
+    let mut count = 0;
+    // ratings which did not match any rule so far
+    let mut rest = ratings;
+    for rule in &workflows[wname].rules {
+        match rule {
+            WorkflowRule::Condition(c, op, threshold, target) => {
+                let threshold = *threshold as u64;
+                let (matched, unmatched) = match op {
+                    '<' => rest[c].split_at(threshold),
+                    _ => {
+                        let (below, above) = rest[c].split_at(threshold + 1);
+                        (above, below)
+                    }
+                };
+                if !matched.is_empty() {
+                    let mut next = rest.clone();
+                    next.insert(*c, matched);
+                    count += accepted_count(workflows, target, next);
+                }
+                if unmatched.is_empty() {
+                    return count;
+                }
+                rest.insert(*c, unmatched);
+            }
+            WorkflowRule::Terminate(target) => {
+                return count + accepted_count(workflows, target, rest);
+            }
+        }
+    }
+    count
 }
 
 // Returns Some(workflow) if matched or None if not
-fn match_rule<'a>(part: &Part, rule: &'a WorkflowRule) -> Option<&'a String> {
+pub fn match_rule<'a>(part: &Part, rule: &'a WorkflowRule) -> Option<&'a String> {
     match rule {
         WorkflowRule::Condition(c, op, threshold, wname) => {
             let rating = part.ratings[c];
@@ -66,12 +119,12 @@ fn match_rule<'a>(part: &Part, rule: &'a WorkflowRule) -> Option<&'a String> {
 }
 
 #[derive(Debug, PartialEq, Eq)]
-struct Part {
-    ratings: HashMap<char, u32>,
+pub struct Part {
+    pub ratings: HashMap<char, u32>,
 }
 
 #[derive(Debug, PartialEq, Eq)]
-enum WorkflowRule {
+pub enum WorkflowRule {
     // category, op, threshold, target workflow
     Condition(char, char, u32, String),
     // target workflow
@@ -79,78 +132,257 @@ enum WorkflowRule {
 }
 
 #[derive(Debug, PartialEq, Eq)]
-struct Workflow {
-    name: String,
-    rules: Vec<WorkflowRule>,
+pub struct Workflow {
+    pub name: String,
+    pub rules: Vec<WorkflowRule>,
 }
 
-struct Input {
-    workflows: HashMap<String, Workflow>,
-    parts: Vec<Part>,
+pub struct Input {
+    pub workflows: HashMap<String, Workflow>,
+    pub parts: Vec<Part>,
 }
 
-fn parse_workflow(line: &str) -> Workflow {
+pub fn parse_workflow(line: &str) -> Result<Workflow, ParseError> {
     // px{a<2006:qkq,m>2090:A,rfg}
-    let start_idx = line.find('{').unwrap();
+    let start_idx = find(line, line, '{')?;
     let name = &line[..start_idx];
-    let rules: Vec<_> = line[start_idx + 1..line.len() - 1]
+    if name.is_empty() {
+        return Err(ParseError::new(line, name, "workflow name"));
+    }
+    let rules_str = line[start_idx + 1..]
+        .strip_suffix('}')
+        .ok_or_else(|| ParseError::at_end(line, "'}'"))?;
+    let rules: Vec<_> = rules_str
         .split(',')
-        .map(|rule_str| {
-            if rule_str.contains(['<', '>']) {
-                let mut rule_chars = rule_str.chars();
-                let col_pos = rule_str.find(':').unwrap();
-                WorkflowRule::Condition(
-                    rule_chars.next().unwrap(),
-                    rule_chars.next().unwrap(),
-                    rule_str[2..col_pos].parse().unwrap(),
-                    rule_str[col_pos + 1..].to_string(),
-                )
-            } else {
-                WorkflowRule::Terminate(rule_str.to_string())
-            }
-        })
-        .collect();
-    Workflow {
+        .map(|rule_str| parse_rule(line, rule_str))
+        .collect::<Result<_, _>>()?;
+    Ok(Workflow {
         name: name.to_string(),
-        rules: rules,
+        rules,
+    })
+}
+
+// Parse `rule_str`, which is a slice of the workflow `line`
+fn parse_rule(line: &str, rule_str: &str) -> Result<WorkflowRule, ParseError> {
+    if rule_str.contains(['<', '>']) {
+        let mut rule_chars = rule_str.chars();
+        let category = rule_chars.next().unwrap();
+        if !"xmas".contains(category) {
+            return Err(ParseError::new(
+                line,
+                &rule_str[..category.len_utf8()],
+                "category x, m, a or s",
+            ));
+        }
+        let op = rule_chars.next().unwrap();
+        if op != '<' && op != '>' {
+            // the category is one of "xmas" here, so it takes a single byte
+            return Err(ParseError::new(
+                line,
+                &rule_str[1..1 + op.len_utf8()],
+                "'<' or '>'",
+            ));
+        }
+        let col_pos = find(line, rule_str, ':')?;
+        Ok(WorkflowRule::Condition(
+            category,
+            op,
+            parse_num(line, &rule_str[2..col_pos])?,
+            rule_str[col_pos + 1..].to_string(),
+        ))
+    } else if rule_str.is_empty() {
+        Err(ParseError::new(line, rule_str, "rule"))
+    } else {
+        Ok(WorkflowRule::Terminate(rule_str.to_string()))
     }
 }
 
-fn parse_part(line: &str) -> Part {
+pub fn parse_part(line: &str) -> Result<Part, ParseError> {
+    // {x=787,m=2655,a=1222,s=2876}
+    let ratings_str = line
+        .strip_prefix('{')
+        .ok_or_else(|| ParseError::new(line, line, "'{'"))?
+        .strip_suffix('}')
+        .ok_or_else(|| ParseError::at_end(line, "'}'"))?;
     let mut ratings: HashMap<char, u32> = HashMap::new();
-    line[1..line.len() - 1]
-        .split(',')
-        .map(|rat_str| {
-            (
-                rat_str.chars().nth(0).unwrap(),
-                rat_str[2..].parse::<u32>().unwrap(),
-            )
+    for rat_str in ratings_str.split(',') {
+        let eq_pos = find(line, rat_str, '=')?;
+        let category = &rat_str[..eq_pos];
+        let c = match category {
+            "x" | "m" | "a" | "s" => category.chars().next().unwrap(),
+            _ => return Err(ParseError::new(line, category, "category x, m, a or s")),
+        };
+        if ratings.contains_key(&c) {
+            return Err(ParseError::new(line, category, "each category once"));
+        }
+        ratings.insert(c, parse_num(line, &rat_str[eq_pos + 1..])?);
+    }
+    // the rules may test any category, so a part needs all of them
+    if let Some(c) = "xmas".chars().find(|c| !ratings.contains_key(c)) {
+        let end = &line[line.len() - 1..];
+        return Err(ParseError::new(line, end, format!("rating of '{}'", c)));
+    }
+    Ok(Part { ratings })
+}
+
+pub fn load_input(input_path: &Path) -> Result<Input> {
+    input::load(input_path, parse_input)
+}
+
+// `size` workflows, at most 10000, and `size` parts.
+// Rules send parts only to workflows defined after them, so there are no cycles
+// and every part ends up accepted or rejected.
+pub fn generate(rng: &mut Rng, size: usize) -> Vec<String> {
+    let size = size.clamp(1, 10_000);
+    let mut names = vec!["in".to_string()];
+    let mut used: HashSet<String> = names.iter().cloned().collect();
+    while names.len() < size {
+        let len = rng.range(2..4) as usize;
+        let name = rng.string("abcdefghijklmnopqrstuvwxyz", len);
+        if used.insert(name.clone()) {
+            names.push(name);
+        }
+    }
+    let target = |rng: &mut Rng, idx: usize| -> String {
+        if idx + 1 < names.len() && rng.chance(0.6) {
+            names[rng.range(idx as u64 + 1..names.len() as u64) as usize].clone()
+        } else if rng.chance(0.5) {
+            "A".to_string()
+        } else {
+            "R".to_string()
+        }
+    };
+    let mut workflows: Vec<String> = (0..names.len())
+        .map(|idx| {
+            let rules: Vec<String> = (0..rng.range(1..5))
+                .map(|_| {
+                    let category = *rng.choose(&['x', 'm', 'a', 's']);
+                    let op = *rng.choose(&['<', '>']);
+                    let threshold = rng.range(1..4001);
+                    format!("{}{}{}:{}", category, op, threshold, target(rng, idx))
+                })
+                .collect();
+            format!("{}{{{},{}}}", names[idx], rules.join(","), target(rng, idx))
         })
-        .for_each(|(c, r)| {
-            ratings.insert(c, r);
-        });
-    Part { ratings }
+        .collect();
+    rng.shuffle(&mut workflows);
+    let mut lines = workflows;
+    lines.push(String::new());
+    for _ in 0..size {
+        let ratings: Vec<String> = ["x", "m", "a", "s"]
+            .iter()
+            .map(|c| format!("{}={}", c, rng.range(1..4001)))
+            .collect();
+        lines.push(format!("{{{}}}", ratings.join(",")));
+    }
+    lines
+}
+
+pub fn check(input_path: &Path) -> Result<Vec<ParseError>> {
+    let lines = input::load(input_path, input::read_lines)?;
+    Ok(parse_lines(&lines).1)
 }
 
-fn load_input(input_path: &Path) -> io::Result<Input> {
-    let file = File::open(input_path)?;
-    let buf_reader = io::BufReader::new(file);
-    let mut lines = buf_reader.lines();
+pub fn parse_input(reader: impl BufRead) -> Result<Input> {
+    let lines = input::read_lines(reader)?;
+    let (input, problems) = parse_lines(&lines);
+    match problems.into_iter().next() {
+        Some(e) => Err(e.into()),
+        None => Ok(input),
+    }
+}
+
+// Parse workflows and parts going on after bad lines, so every problem is found:
+// lines which do not parse, references to undefined workflows, a missing "in" workflow
+// and rules which send parts around in a loop
+fn parse_lines(lines: &[String]) -> (Input, Vec<ParseError>) {
+    let mut problems = Vec::new();
     let mut workflows: HashMap<String, Workflow> = HashMap::new();
     let mut parts: Vec<_> = vec![];
-    while let Some(line) = lines.next() {
-        let line = line?;
+    // (line index, target) of every rule
+    let mut references = Vec::new();
+    // line index of every workflow
+    let mut defined: HashMap<&str, usize> = HashMap::new();
+    let mut numbered = lines.iter().enumerate();
+    let mut separator = lines.len();
+    for (idx, line) in numbered.by_ref() {
         if line.is_empty() {
+            separator = idx;
             break;
         }
-        let workflow = parse_workflow(&line);
-        workflows.insert(workflow.name.clone(), workflow);
+        match parse_workflow(line) {
+            Ok(workflow) => {
+                references.extend(rule_targets(line).map(|target| (idx, target)));
+                defined.insert(&line[..workflow.name.len()], idx);
+                workflows.insert(workflow.name.clone(), workflow);
+            }
+            Err(e) => problems.push(e.on_line(idx + 1)),
+        }
     }
-    while let Some(line) = lines.next() {
-        let line = line?;
-        parts.push(parse_part(&line));
+    for (idx, line) in numbered {
+        match parse_part(line) {
+            Ok(part) => parts.push(part),
+            Err(e) => problems.push(e.on_line(idx + 1)),
+        }
+    }
+    for (idx, target) in references {
+        if target != "A" && target != "R" && !workflows.contains_key(target) {
+            let line = &lines[idx];
+            problems.push(ParseError::new(line, target, "defined workflow").on_line(idx + 1));
+        }
+    }
+    if !workflows.contains_key("in") {
+        let line = lines.get(separator).map(|l| l.as_str()).unwrap_or("");
+        problems.push(ParseError::at_end(line, "workflow 'in'").on_line(separator + 1));
+    }
+    problems.extend(loop_problems(lines, &defined));
+    problems.sort_by_key(|e| (e.line, e.column));
+    (Input { workflows, parts }, problems)
+}
+
+// Rules which send parts back to a workflow they went through, found by walking
+// the workflows from "in" depth first. A loop would keep the solvers going forever.
+fn loop_problems(lines: &[String], defined: &HashMap<&str, usize>) -> Vec<ParseError> {
+    let mut problems = Vec::new();
+    let Some(&start) = defined.get("in") else {
+        return problems;
+    };
+    // workflows on the walk so far map to true, the ones walked through map to false
+    let mut on_walk: HashMap<&str, bool> = HashMap::from([("in", true)]);
+    // workflows on the walk with their line index and the targets left to follow
+    let mut walk = vec![("in", start, rule_targets(&lines[start]))];
+    while let Some((name, idx, targets)) = walk.last_mut() {
+        let (name, idx) = (*name, *idx);
+        let Some(target) = targets.next() else {
+            on_walk.insert(name, false);
+            walk.pop();
+            continue;
+        };
+        match (on_walk.get(target), defined.get(target)) {
+            (Some(true), _) => {
+                let expected = format!("workflow which does not lead back to '{}'", name);
+                problems.push(ParseError::new(&lines[idx], target, expected).on_line(idx + 1));
+            }
+            (None, Some(&next)) => {
+                on_walk.insert(target, true);
+                walk.push((target, next, rule_targets(&lines[next])));
+            }
+            // walked through already, "A", "R" or undefined
+            _ => {}
+        }
     }
-    Ok(Input { workflows, parts })
+    problems
+}
+
+// Target workflows of the rules, as slices of the workflow `line`
+fn rule_targets(line: &str) -> impl Iterator<Item = &str> {
+    let rules = line
+        .find('{')
+        .map(|start| line[start + 1..].trim_end_matches('}'))
+        .unwrap_or("");
+    rules
+        .split(',')
+        .map(|rule| rule.rsplit(':').next().unwrap())
 }
 
 #[cfg(test)]
@@ -162,13 +394,27 @@ mod tests {
         let expected_ratings: HashMap<char, u32> =
             HashMap::from([('x', 787), ('m', 2655), ('a', 1222), ('s', 2876)]);
         assert_eq!(
-            Part {
+            Ok(Part {
                 ratings: expected_ratings
-            },
+            }),
             parse_part("{x=787,m=2655,a=1222,s=2876}")
         );
     }
 
+    #[test]
+    fn test_parse_part_needs_each_category_once() {
+        let e = parse_part("{x=1,m=2,x=3,s=4}").unwrap_err();
+        assert_eq!(
+            (10, "each category once", "'x'"),
+            (e.column, e.expected.as_str(), e.found.as_str())
+        );
+        let e = parse_part("{x=1,m=2,s=4}").unwrap_err();
+        assert_eq!(
+            ("rating of 'a'", "'}'"),
+            (e.expected.as_str(), e.found.as_str())
+        );
+    }
+
     #[test]
     fn test_parse_workfow() {
         let expected_workflow = Workflow {
@@ -180,8 +426,71 @@ mod tests {
             ],
         };
         assert_eq!(
-            expected_workflow,
+            Ok(expected_workflow),
             parse_workflow("px{a<2006:qkq,m>2090:A,rfg}")
         );
     }
+
+    #[test]
+    fn test_parse_non_ascii_rule() {
+        let e = parse_workflow("in{é<5:A,R}").unwrap_err();
+        assert_eq!(
+            ("category x, m, a or s", "'é'"),
+            (e.expected.as_str(), e.found.as_str())
+        );
+        let e = parse_workflow("in{xé<5:A,R}").unwrap_err();
+        assert_eq!(
+            ("'<' or '>'", "'é'"),
+            (e.expected.as_str(), e.found.as_str())
+        );
+    }
+
+    #[test]
+    fn test_problems() {
+        let lines: Vec<String> = ["in{x<5:zz,A}", "px{a<:A,R}", "", "{x=1}", "{y=2}"]
+            .iter()
+            .map(|l| l.to_string())
+            .collect();
+        let (_, problems) = parse_lines(&lines);
+        let found: Vec<_> = problems
+            .iter()
+            .map(|e| (e.line, e.expected.as_str(), e.found.as_str()))
+            .collect();
+        assert_eq!(
+            vec![
+                (1, "defined workflow", "'zz'"),
+                (2, "number", "end of line"),
+                (4, "rating of 'm'", "'}'"),
+                (5, "category x, m, a or s", "'y'"),
+            ],
+            found
+        );
+    }
+
+    #[test]
+    fn test_loops() {
+        let lines: Vec<String> = [
+            "in{x<5:px,A}",
+            "px{a<3:qq,R}",
+            "qq{s>1:px,in}",
+            // not reached from "in"
+            "aa{m>2:bb,A}",
+            "bb{aa}",
+            "",
+            "{x=1,m=2,a=3,s=4}",
+        ]
+        .iter()
+        .map(|l| l.to_string())
+        .collect();
+        let (_, problems) = parse_lines(&lines);
+        let found: Vec<_> = problems
+            .iter()
+            .map(|e| (e.line, e.column, e.found.as_str()))
+            .collect();
+        assert_eq!(vec![(3, 8, "'px'"), (3, 11, "'in'")], found);
+        assert_eq!(
+            "workflow which does not lead back to 'qq'",
+            problems[0].expected
+        );
+    }
 }
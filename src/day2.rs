@@ -1,21 +1,27 @@
+use crate::answer::Answer;
+use crate::error::{ParseError, Result};
+use crate::input;
+use crate::parse::{
+    keyword, literal, map, pair, parse_line, preceded, separated, terminated, uint, Parser,
+};
+use crate::rng::Rng;
 use std::cmp::max;
-use std::io;
 use std::io::BufRead;
-use std::{fs::File, path::Path};
+use std::path::Path;
 
-pub fn part1(input_path: &Path) {
-    let input = parse_input(input_path).unwrap();
+pub fn part1(input_path: &Path) -> Result<Answer> {
+    let input = load_input(input_path)?;
     // now we search for games which has no more than 12r, 13g, 14b in each hand
     let result: u32 = input
         .iter()
         .filter(|g| g.sets.iter().all(|h| h.r <= 12 && h.g <= 13 && h.b <= 14))
         .map(|g| g.id)
         .sum();
-    println!("Result: {}", result);
+    Ok(result.into())
 }
 
-pub fn part2(input_path: &Path) {
-    let input = parse_input(input_path).unwrap();
+pub fn part2(input_path: &Path) -> Result<Answer> {
+    let input = load_input(input_path)?;
     // first we calculate the minimal cube set for each game
     let result: u32 = input
         .iter()
@@ -30,53 +36,75 @@ pub fn part2(input_path: &Path) {
         })
         .map(|set| set.r * set.g * set.b)
         .sum();
-    println!("Result: {}", result);
+    Ok(result.into())
 }
 
 #[derive(Debug)]
-struct CubeSet {
-    r: u32,
-    g: u32,
-    b: u32,
+pub struct CubeSet {
+    pub r: u32,
+    pub g: u32,
+    pub b: u32,
 }
 
 #[derive(Debug)]
-struct Game {
-    id: u32,
-    sets: Vec<CubeSet>,
+pub struct Game {
+    pub id: u32,
+    pub sets: Vec<CubeSet>,
 }
 
-fn parse_hand(hand_str: &str) -> CubeSet {
-    let hand_str = hand_str.trim();
-    let mut r: u32 = 0;
-    let mut g: u32 = 0;
-    let mut b: u32 = 0;
-    for elem in hand_str.split(',') {
-        let elem = elem.trim();
-        let num: u32 = elem[0..(elem.find(" ").unwrap())].parse().unwrap();
-        if elem.ends_with("red") {
-            r += num;
-        } else if elem.ends_with("green") {
-            g += num;
-        } else if elem.ends_with("blue") {
-            b += num;
+// "3 blue, 4 red": counts of the colors in any order, missing ones are 0
+pub fn cube_set<'a>() -> impl Parser<'a, CubeSet> {
+    let cubes = pair(uint::<u32>, keyword(&["red", "green", "blue"]));
+    map(separated(cubes, literal(",")), |cubes| {
+        let mut set = CubeSet { r: 0, g: 0, b: 0 };
+        for (num, color) in cubes {
+            match color {
+                "red" => set.r += num,
+                "green" => set.g += num,
+                _ => set.b += num,
+            }
         }
-    }
-    return CubeSet { r, g, b };
+        set
+    })
 }
 
-fn parse_game(line: String) -> Game {
-    // read game ID: parse a number between "Game " and ":"
-    let id_end_idx = line.find(":").unwrap();
-    let id: u32 = line[5..id_end_idx].parse().unwrap();
-    // now we can split remaining line by ';' and parse each hand separately
-    let hands_str = &line[id_end_idx + 1..line.len()];
-    let hands = hands_str.split(';').map(parse_hand).collect();
-    Game { id, sets: hands }
+// "Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue"
+pub fn parse_game(line: &str) -> Result<Game, ParseError> {
+    let id = preceded(literal("Game"), terminated(uint::<u32>, literal(":")));
+    let sets = separated(cube_set(), literal(";"));
+    let (id, sets) = parse_line(line, pair(id, sets))?;
+    Ok(Game { id, sets })
 }
 
-fn parse_input(input_path: &Path) -> io::Result<Vec<Game>> {
-    let file = File::open(input_path)?;
-    let buf_reader = io::BufReader::new(file);
-    return buf_reader.lines().map(|res| res.map(parse_game)).collect();
+// `size` games of up to 6 sets with up to 20 cubes of each color
+pub fn generate(rng: &mut Rng, size: usize) -> Vec<String> {
+    (1..=size)
+        .map(|id| {
+            let sets: Vec<String> = (0..rng.range(1..7))
+                .map(|_| {
+                    let mut colors = ["red", "green", "blue"];
+                    rng.shuffle(&mut colors);
+                    let count = rng.range(1..4) as usize;
+                    colors[..count]
+                        .iter()
+                        .map(|color| format!("{} {}", rng.range(1..21), color))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                })
+                .collect();
+            format!("Game {}: {}", id, sets.join("; "))
+        })
+        .collect()
+}
+
+pub fn check(input_path: &Path) -> Result<Vec<ParseError>> {
+    input::check_lines(input_path, parse_game)
+}
+
+pub fn load_input(input_path: &Path) -> Result<Vec<Game>> {
+    input::load(input_path, parse_input)
+}
+
+pub fn parse_input(reader: impl BufRead) -> Result<Vec<Game>> {
+    input::parse_lines(reader, parse_game)
 }
@@ -1,131 +1,106 @@
-use std::cmp::{max, min};
-use std::io;
+use crate::answer::Answer;
+use crate::error::{ParseError, Result};
+use crate::grid::Grid;
+use crate::input;
+use crate::rng::Rng;
+use crate::{debug, trace};
 use std::io::BufRead;
-use std::{fs::File, path::Path};
+use std::path::Path;
 
-pub fn part1(input_path: &Path) {
-    let input = load_input(input_path).unwrap();
+pub fn part1(input_path: &Path) -> Result<Answer> {
+    let input = load_input(input_path)?;
     // first we scan for all possible number positions
     let num_positions = parse_num_positions(&input);
     // debug: print all found numbers
-    eprintln!("Input:");
-    for row in &input {
-        eprintln!("{}", row);
-    }
-    eprintln!("Found numbers: ");
+    debug!("Input:\n{}", input);
+    debug!("Found numbers: ");
     for num_pos in &num_positions {
-        let row = &input[num_pos.row];
-        let num_str = &row[num_pos.start_col..num_pos.end_col];
-        eprintln!("{:?}: {}", num_pos, num_str)
+        debug!("{:?}: {}", num_pos, parse_number(&input, num_pos))
     }
 
     // now we go over all num positions and check if they are surrounded by parts
     let mut part_numbers: Vec<u32> = Vec::new();
-    'loop_by_positions: for pos in &num_positions {
-        // let's build a range: which rows and cols to scan
-        let start_col = max(0, pos.start_col as i32 - 1) as usize;
-        let end_col = min(input[pos.row].len(), pos.end_col + 1);
-        let start_row = max(0, pos.row as i32 - 1) as usize;
-        // pos.row + 2, because we need to scan the row next to the current one's
-        let end_row = min(input.len(), pos.row + 2);
-        eprintln!(
-            "Position: {:?}\nscan rows: {}..{}, cols: {}..{}",
-            pos, start_row, end_row, start_col, end_col
-        );
-        // now let's scan for part characters
-        for row_idx in start_row..end_row {
-            let row = &input[row_idx];
-            eprintln!("Scanning string: {}", &row[start_col..end_col]);
-            if row[start_col..end_col].chars().any(is_part) {
-                part_numbers.push(parse_number(&input, pos));
-                continue 'loop_by_positions;
-            }
+    for pos in &num_positions {
+        if pos
+            .cells()
+            .flat_map(|cell| input.neighbors8(cell))
+            .any(|neighbor| is_part(input[neighbor]))
+        {
+            trace!("Position {:?} is next to a part", pos);
+            part_numbers.push(parse_number(&input, pos));
         }
     }
-    eprintln!("Part numbers: {:?}", &part_numbers);
-    println!("Result: {}", part_numbers.iter().sum::<u32>());
+    debug!("Part numbers: {:?}", &part_numbers);
+    Ok(part_numbers.iter().sum::<u32>().into())
 }
 
-pub fn part2(input_path: &Path) {
-    let input = load_input(input_path).unwrap();
+pub fn part2(input_path: &Path) -> Result<Answer> {
+    let input = load_input(input_path)?;
     let num_positions = parse_num_positions(&input);
-    for line in &input {
-        eprintln!("{}", line);
-    }
-    // scan rows for positions of gears
+    debug!("Input:\n{}", input);
+    // scan for positions of gears
     // for each gear find adjacent nums
     let mut gear_ratios = Vec::new();
-    for (row, line) in input.iter().enumerate() {
-        for (col, c) in line.char_indices() {
-            if c == '*' {
-                // check adjacent numbers
-                // first build window of surrounding idx'es
-                let start_col = max(0, col as i32 - 1) as usize;
-                // col + 2, because we need to scan the col next to the current one's
-                let end_col = min(line.len(), col + 2);
-                let start_row = max(0, row as i32 - 1) as usize;
-                // row + 2, because we need to scan the row next to the current one's
-                let end_row = min(input.len(), row + 2);
-                eprintln!(
-                    "Found '*' at {}, {}. Surrounding window rows: {}..{}, cols: {}..{}",
-                    row, col, start_row, end_row, start_col, end_col
-                );
-                // how to check it? we need to scan list of numbers and check if any of them has row within given
-                // and col within given
-                let mut gear_nums = Vec::new();
-                for pos in &num_positions {
-                    if pos.row >= start_row
-                        && pos.row < end_row
-                        // does number begin in window
-                        && (pos.start_col >= start_col && pos.start_col < end_col
-                            // or ends in it
-                            || pos.end_col - 1 >= start_col && pos.end_col - 1 < end_col)
-                    {
-                        eprintln!("Number {:?} overlaps with this gear", pos);
-                        gear_nums.push(parse_number(&input, pos));
-                    }
-                }
-                if gear_nums.len() == 2 {
-                    let gear_ratio = gear_nums[0] * gear_nums[1];
-                    eprintln!("This part is a gear! Ratio: {}", gear_ratio);
-                    gear_ratios.push(gear_ratio);
-                }
-            }
+    for (gear, _) in input.iter().filter(|(_, c)| **c == '*') {
+        trace!("Found '*' at {:?}", gear);
+        // a number is adjacent when one of its cells is a neighbor of the gear
+        let neighbors: Vec<(usize, usize)> = input.neighbors8(gear).collect();
+        let gear_nums: Vec<u32> = num_positions
+            .iter()
+            .filter(|pos| pos.cells().any(|cell| neighbors.contains(&cell)))
+            .map(|pos| {
+                trace!("Number {:?} overlaps with this gear", pos);
+                parse_number(&input, pos)
+            })
+            .collect();
+        if gear_nums.len() == 2 {
+            let gear_ratio = gear_nums[0] * gear_nums[1];
+            debug!("This part is a gear! Ratio: {}", gear_ratio);
+            gear_ratios.push(gear_ratio);
         }
     }
     let result: u32 = gear_ratios.iter().sum();
-    println!("Result: {}", result);
+    Ok(result.into())
 }
 
+// Digits, empty cells and every symbol which could be a part
+const SCHEMATIC: &str = "0123456789.!\"#$%&'()*+,-/:;<=>?@[\\]^_`{|}~";
+
 #[derive(Debug)]
-struct NumPosition {
-    row: usize,
-    start_col: usize,
-    end_col: usize,
+pub struct NumPosition {
+    pub row: usize,
+    pub start_col: usize,
+    pub end_col: usize,
+}
+
+impl NumPosition {
+    // Positions of the digits of the number
+    pub fn cells(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        (self.start_col..self.end_col).map(|col| (self.row, col))
+    }
 }
 
-fn is_part(c: char) -> bool {
-    c != '.' && !c.is_digit(10)
+pub fn is_part(c: char) -> bool {
+    c != '.' && !c.is_ascii_digit()
 }
 
-fn parse_num_positions(input: &Vec<String>) -> Vec<NumPosition> {
+pub fn parse_num_positions(input: &Grid<char>) -> Vec<NumPosition> {
     let mut num_positions: Vec<NumPosition> = Vec::new();
-    for row_idx in 0..input.len() {
-        let row = &input[row_idx];
+    for (row_idx, row) in input.rows().enumerate() {
         // we need current num start, end
         let mut num_start: Option<usize> = None;
-        for (col_idx, c) in row.char_indices() {
-            if c.is_digit(10) {
+        for (col_idx, c) in row.iter().enumerate() {
+            if c.is_ascii_digit() {
                 if num_start.is_none() {
                     // first time we encounter number
                     num_start = Some(col_idx);
                 }
             } else {
-                if num_start.is_some() {
+                if let Some(start_col) = num_start {
                     // number is finished
                     num_positions.push(NumPosition {
                         row: row_idx,
-                        start_col: num_start.unwrap(),
+                        start_col,
                         end_col: col_idx,
                     });
                     num_start = None;
@@ -133,11 +108,11 @@ fn parse_num_positions(input: &Vec<String>) -> Vec<NumPosition> {
             }
         }
         // the case for number at the end of the row
-        if num_start.is_some() {
+        if let Some(start_col) = num_start {
             // number is finished
             num_positions.push(NumPosition {
                 row: row_idx,
-                start_col: num_start.unwrap(),
+                start_col,
                 end_col: row.len(),
             });
         }
@@ -145,14 +120,43 @@ fn parse_num_positions(input: &Vec<String>) -> Vec<NumPosition> {
     num_positions
 }
 
-fn parse_number(input: &Vec<String>, position: &NumPosition) -> u32 {
-    let row = &input[position.row];
-    row[position.start_col..position.end_col].parse().unwrap()
+pub fn parse_number(input: &Grid<char>, position: &NumPosition) -> u32 {
+    let row = input.row(position.row);
+    String::from_iter(&row[position.start_col..position.end_col])
+        .parse()
+        .unwrap()
+}
+
+// `size` x `size` schematic with numbers of up to 3 digits and symbols between dots
+pub fn generate(rng: &mut Rng, size: usize) -> Vec<String> {
+    (0..size)
+        .map(|_| {
+            let mut row = String::with_capacity(size);
+            while row.len() < size {
+                // a number never follows another one, they would merge
+                if rng.chance(0.15) && !row.ends_with(|c: char| c.is_ascii_digit()) {
+                    let len = (rng.range(1..4) as usize).min(size - row.len());
+                    row.push_str(&rng.range(1..10).to_string());
+                    row.push_str(&rng.string("0123456789", len - 1));
+                } else if rng.chance(0.1) {
+                    row.push(*rng.choose(&['*', '#', '+', '$', '/', '@', '=', '%', '&', '-']));
+                } else {
+                    row.push('.');
+                }
+            }
+            row
+        })
+        .collect()
+}
+
+pub fn check(input_path: &Path) -> Result<Vec<ParseError>> {
+    input::check_grid(input_path, SCHEMATIC)
+}
+
+pub fn load_input(input_path: &Path) -> Result<Grid<char>> {
+    input::load(input_path, parse_input)
 }
 
-fn load_input(input_path: &Path) -> io::Result<Vec<String>> {
-    let file = File::open(input_path)?;
-    let buf_reader = io::BufReader::new(file);
-    // return buf_reader.lines().map(|res| res.map(parse_game)).collect();
-    return buf_reader.lines().collect();
+pub fn parse_input(reader: impl BufRead) -> Result<Grid<char>> {
+    input::parse_grid(reader, SCHEMATIC)
 }
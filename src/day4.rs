@@ -1,16 +1,21 @@
+use crate::answer::Answer;
+use crate::error::{ParseError, Result};
+use crate::input;
+use crate::parse::{list, literal, pair, parse_line, preceded, terminated, uint};
+use crate::rng::Rng;
+use crate::{debug, trace};
 use std::collections::HashSet;
-use std::fs::File;
-use std::io::{self, BufRead};
+use std::io::BufRead;
 use std::path::Path;
 
-pub fn part1(input_path: &Path) {
-    let cards = load_input(input_path).unwrap();
-    eprintln!("Loaded cards:");
+pub fn part1(input_path: &Path) -> Result<Answer> {
+    let cards = load_input(input_path)?;
+    debug!("Loaded cards:");
     for card in &cards {
-        eprintln!("{:?}", card);
+        debug!("{:?}", card);
     }
     // for each card we calculate a number
-    let result: u32 = (&cards)
+    let result: u32 = cards
         .iter()
         .map(|card| {
             let win_c: HashSet<u8> = HashSet::from_iter(card.winning_nums.iter().cloned());
@@ -19,14 +24,14 @@ pub fn part1(input_path: &Path) {
         })
         .map(|n| if n == 0 { 0 } else { 2u32.pow(n as u32 - 1) })
         .sum();
-    println!("Result: {}", result);
+    Ok(result.into())
 }
 
-pub fn part2(input_path: &Path) {
-    let cards = load_input(input_path).unwrap();
-    eprintln!("Loaded cards:");
+pub fn part2(input_path: &Path) -> Result<Answer> {
+    let cards = load_input(input_path)?;
+    debug!("Loaded cards:");
     for card in &cards {
-        eprintln!("{:?}", card);
+        debug!("{:?}", card);
     }
     // for each card we first count matching numbers (MC) and update next MC cards
     let mut copy_cards: Vec<_> = cards.iter().map(CopyCard::new).collect();
@@ -39,13 +44,13 @@ pub fn part2(input_path: &Path) {
         }
     }
     let result: u32 = copy_cards.iter().map(|c| c.copies).sum();
-    println!("Result: {}", result);
+    Ok(result.into())
 }
 
 #[derive(Debug)]
-struct Card {
-    winning_nums: Vec<u8>,
-    present_nums: Vec<u8>,
+pub struct Card {
+    pub winning_nums: Vec<u8>,
+    pub present_nums: Vec<u8>,
 }
 
 struct CopyCard<'a> {
@@ -70,29 +75,61 @@ impl<'a> CopyCard<'a> {
     }
 }
 
-fn parse_card(s: String) -> Card {
-    eprintln!("String: '{}'", s);
-    let colon_idx = s.find(":").unwrap();
-    let bar_idx = s.find("|").unwrap();
-    let winning_nums: Vec<_> = (&s[colon_idx + 2..bar_idx - 1])
-        .split(' ')
-        .filter(|s| !s.is_empty())
-        .map(|ns| ns.parse::<u8>().unwrap())
-        .collect();
-    let present_nums: Vec<_> = (&s[bar_idx + 2..s.len()])
-        .split(' ')
-        .filter(|s| !s.is_empty())
-        .map(|ns| ns.parse::<u8>().unwrap())
-        .collect();
-    Card {
+// "Card 1: 41 48 83 | 83 86  6 31 17"
+pub fn parse_card(line: &str) -> Result<Card, ParseError> {
+    trace!("String: '{}'", line);
+    let id = pair(literal("Card"), pair(uint::<u32>, literal(":")));
+    let nums = pair(terminated(list(uint::<u8>), literal("|")), list(uint::<u8>));
+    let (winning_nums, present_nums) = parse_line(line, preceded(id, nums))?;
+    Ok(Card {
         winning_nums,
         present_nums,
-    }
+    })
+}
+
+// `size` cards with 5 winning numbers and 8 numbers we have.
+// A card wins only every 4th time and never past the last card,
+// so the number of copies in part 2 stays moderate.
+pub fn generate(rng: &mut Rng, size: usize) -> Vec<String> {
+    let width = size.to_string().len();
+    (1..=size)
+        .map(|id| {
+            let mut nums: Vec<u64> = (1..100).collect();
+            rng.shuffle(&mut nums);
+            let max_wins = (size - id).min(5) as u64;
+            let wins = if rng.chance(0.25) {
+                rng.range(0..max_wins + 1) as usize
+            } else {
+                0
+            };
+            let mut have: Vec<u64> = nums[..wins].to_vec();
+            have.extend(&nums[5..13 - wins]);
+            rng.shuffle(&mut have);
+            let format_nums = |nums: &[u64]| {
+                nums.iter()
+                    .map(|n| format!("{:>2}", n))
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            };
+            format!(
+                "Card {:>width$}: {} | {}",
+                id,
+                format_nums(&nums[..5]),
+                format_nums(&have),
+                width = width
+            )
+        })
+        .collect()
+}
+
+pub fn check(input_path: &Path) -> Result<Vec<ParseError>> {
+    input::check_lines(input_path, parse_card)
+}
+
+pub fn load_input(input_path: &Path) -> Result<Vec<Card>> {
+    input::load(input_path, parse_input)
 }
 
-fn load_input(input_path: &Path) -> io::Result<Vec<Card>> {
-    let file = File::open(input_path)?;
-    let buf_reader = io::BufReader::new(file);
-    // return buf_reader.lines().map(|res| res.map(parse_game)).collect();
-    return buf_reader.lines().map(|r| r.map(parse_card)).collect();
+pub fn parse_input(reader: impl BufRead) -> Result<Vec<Card>> {
+    input::parse_lines(reader, parse_card)
 }
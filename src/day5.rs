@@ -1,189 +1,160 @@
+use crate::answer::Answer;
+use crate::error::{ParseError, Result};
+use crate::input;
+use crate::interval::IntervalSet;
+use crate::parse::{self, list, literal, map, pair, uint, word, Section};
+use crate::progress::Progress;
+use crate::rng::Rng;
+use crate::{debug, trace};
 use rayon::iter::IntoParallelRefIterator;
 use rayon::prelude::*;
 use std::cmp::Ordering;
 use std::fmt::Display;
-use std::fs::File;
-use std::io::{self, BufRead};
+use std::io::BufRead;
 use std::ops::Range;
 use std::path::Path;
-use std::time::Instant;
 
-pub fn part1(input_path: &Path) {
-    let almanac = load_input(input_path).unwrap();
-    eprintln!("Loaded almanac:");
-    eprintln!("{}", &almanac);
+pub fn part1(input_path: &Path) -> Result<Answer> {
+    let almanac = load_input(input_path)?;
+    debug!("Loaded almanac:");
+    debug!("{}", &almanac);
     let result = almanac
         .seeds
         .iter()
         .map(|seed| map_to_location(*seed, &almanac.rule_book))
         .min();
-    println!("Result: {}", result.unwrap())
+    Ok(result.unwrap().into())
 }
 
-pub fn part2(input_path: &Path) {
-    let start = Instant::now();
-    let almanac = load_input(input_path).unwrap();
-    eprintln!("Loaded almanac:");
-    eprintln!("{}", &almanac);
+pub fn part2(input_path: &Path) -> Result<Answer> {
+    let almanac = load_input(input_path)?;
+    debug!("Loaded almanac:");
+    debug!("{}", &almanac);
+    let progress = Progress::new(seed_count(&almanac.seeds));
     let result = almanac
         .seeds
         .chunks(2)
         .map(|c| c[0]..(c[0] + c[1]))
         .enumerate()
         .map(|(i, seed_range)| {
-            eprintln!(
+            debug!(
                 "Start seed range {} of {}: start={}, length={}",
                 i,
                 almanac.seeds.len() / 2,
                 &seed_range.start,
-                &seed_range.end - &seed_range.start
+                seed_range.end - seed_range.start
             );
-            eprintln!("Total running time before: {:?}", start.elapsed());
-            seed_range
-                .map(|seed| map_to_location(seed, &almanac.rule_book))
-                .min()
-                .unwrap()
+            min_location(seed_range, &almanac.rule_book, &progress)
         })
         .min();
 
-    println!("Result: {}", result.unwrap())
+    Ok(result.unwrap().into())
 }
 
 // Part 2 parallelized
-pub fn part3(input_path: &Path) {
-    let start = Instant::now();
-    let almanac = load_input(input_path).unwrap();
-    eprintln!("Loaded almanac:");
-    eprintln!("{}", &almanac);
+pub fn part3(input_path: &Path) -> Result<Answer> {
+    let almanac = load_input(input_path)?;
+    debug!("Loaded almanac:");
+    debug!("{}", &almanac);
     let chunks: Vec<_> = almanac
         .seeds
         .chunks(2)
         .map(|c| c[0]..(c[0] + c[1]))
         .enumerate()
         .collect();
+    let progress = Progress::new(seed_count(&almanac.seeds));
     let result = chunks
         .par_iter()
         .map(|(i, seed_range)| {
-            eprintln!(
+            debug!(
                 "Start seed range {} of {}: start={}, length={}",
                 i,
                 almanac.seeds.len() / 2,
                 &seed_range.start,
-                &seed_range.end - &seed_range.start
+                seed_range.end - seed_range.start
             );
-            eprintln!("Total running time before {}: {:?}", i, start.elapsed());
-            let result = seed_range
-                .clone()
-                .map(|seed| map_to_location(seed, &almanac.rule_book))
-                .min()
-                .unwrap();
-            eprintln!("Total running time after {}: {:?}", i, start.elapsed());
+            let result = min_location(seed_range.clone(), &almanac.rule_book, &progress);
+            debug!("Finished seed range {}", i);
             result
         })
         .min();
 
-    println!("Result: {}", result.unwrap())
+    Ok(result.unwrap().into())
 }
 
-fn apply_rules(seed_ranges: &Vec<Range<u64>>, rule_map: &Vec<RangeRule>) -> Vec<Range<u64>> {
-    // for each range
-    //  for each rule
-    //    if range intersects with rule: extract intersected part and put it in the result
-    // remember the part which was not intersected: it should be added to result as it is
-    // how do we remember part of range which is not affected?
-    // rules do not overlap so we can "nibble" from original range
-    let mut result = Vec::new();
-    for seed_range in seed_ranges {
-        let mut sr = seed_range.clone();
-        for rule in rule_map {
-            if rule.contains(sr.start) || rule.contains(sr.end - 1) {
-                eprintln!("range {:?} intersects with rule {}", sr, &rule);
-                // now split range
-                // there are three possible cases:
-                // - sr is completely covered by rule
-                // - rule bites left part of sr (sr.start is covered by rule)
-                // - rule bites right part of sr (sr.end is covered by rule)
-                if rule.contains(sr.start) {
-                    if rule.contains(sr.end) {
-                        // rule completely matches, early exit
-                        todo!()
-                    } else {
-                        // left bite, continue
-                        // we extract range part and immediately shift it according to rule
-                        let extracted_sr = Range {
-                            start: sr.start - rule.src_key + rule.dst_key,
-                            end: rule.src_key + rule.range_len,
-                        };
-                        result.push(extracted_sr);
-                        // remaining range
-                        sr = Range {
-                            start: rule.src_key + rule.range_len,
-                            end: sr.end,
-                        };
-                    }
-                } else {
-                    // right bite, continue
-                    // we extract range part and immediately shift it according to rule
-                    let extracted_sr = Range {
-                        start: rule.dst_key,
-                        end: sr.end - rule.src_key + rule.dst_key,
-                    };
-                    result.push(extracted_sr);
-                    // remaining range
-                    sr = Range {
-                        start: sr.start,
-                        end: rule.src_key,
-                    };
-                }
-            } else if sr.start <= rule.src_key && sr.end >= rule.src_key + rule.range_len {
-                // special case: seed range contains rule within itself
-                todo!()
-            }
-        }
-        if !sr.is_empty() {
-            result.push(sr);
+// Number of seeds in the seed ranges
+fn seed_count(seeds: &[u64]) -> u64 {
+    seeds.chunks(2).map(|c| c[1]).sum()
+}
+
+// Seeds are mapped in blocks, so progress is reported without slowing down the loop
+const PROGRESS_BLOCK: u64 = 1 << 16;
+
+fn min_location(seeds: Range<u64>, rule_book: &[Vec<RangeRule>], progress: &Progress) -> u64 {
+    (seeds.start..seeds.end)
+        .step_by(PROGRESS_BLOCK as usize)
+        .map(|start| {
+            let end = (start + PROGRESS_BLOCK).min(seeds.end);
+            let result = (start..end)
+                .map(|seed| map_to_location(seed, rule_book))
+                .min()
+                .unwrap();
+            progress.add(end - start);
+            result
+        })
+        .min()
+        .unwrap()
+}
+
+// Keys after one rule map: keys covered by a rule move with it, the rest stay
+fn apply_rules(keys: &IntervalSet, rule_map: &[RangeRule]) -> IntervalSet {
+    let mut unmapped = keys.clone();
+    let mut mapped = IntervalSet::new();
+    for rule in rule_map {
+        let src = IntervalSet::from(rule.src_key..rule.src_key + rule.range_len);
+        let hit = unmapped.intersection(&src);
+        if hit.is_empty() {
+            continue;
         }
+        trace!("{} maps {}", &rule, &hit);
+        mapped = mapped.union(&hit.shift(rule.dst_key as i64 - rule.src_key as i64));
+        unmapped = unmapped.difference(&src);
     }
-    result = Vec::clone(seed_ranges);
-    result.sort_by_key(|r| r.start);
-    result
+    mapped.union(&unmapped)
 }
 
 // Part 2: non-bruteforce solution
-pub fn part4(input_path: &Path) {
-    // the idea is that we go through rule maps keeping only ranges in memory (not seeds)
-    // for each rule map
-    //   new ranges = apply rules to ranges
-    //   ranges = merge new ranges
-    // in the end we take minimal value from minimal range
-    let almanac = load_input(input_path).unwrap();
-    println!("Loaded almanac: {}", &almanac);
-    let seed_ranges: Vec<_> = almanac
+pub fn part4(input_path: &Path) -> Result<Answer> {
+    // only the ranges of keys go through the rule maps, never single seeds
+    let almanac = load_input(input_path)?;
+    debug!("Loaded almanac: {}", &almanac);
+    let mut keys: IntervalSet = almanac
         .seeds
         .chunks(2)
         .map(|c| c[0]..(c[0] + c[1]))
         .collect();
-    println!("Seed ranges: {:?}", &seed_ranges);
-    println!("First step: apply first rule map to seed ranges");
-    let next = apply_rules(&seed_ranges, &almanac.rule_book[0]);
-    println!("Ranges after apply:");
-    println!("{:?}", next);
+    debug!("Seed ranges: {}", &keys);
+    for rule_map in &almanac.rule_book {
+        keys = apply_rules(&keys, rule_map);
+        debug!("Ranges after rule map: {}", &keys);
+    }
+    Ok(keys.min().unwrap().into())
 }
 
-struct RangeRule {
-    src_key: u64,
-    dst_key: u64,
-    range_len: u64,
+pub struct RangeRule {
+    pub src_key: u64,
+    pub dst_key: u64,
+    pub range_len: u64,
 }
 
-struct Almanac {
-    seeds: Vec<u64>,
-    rule_book: Vec<Vec<RangeRule>>,
+pub struct Almanac {
+    pub seeds: Vec<u64>,
+    pub rule_book: Vec<Vec<RangeRule>>,
 }
 
 impl RangeRule {
     fn contains(&self, key: u64) -> bool {
-        return self.src_key <= key && key < self.src_key + self.range_len;
+        self.src_key <= key && key < self.src_key + self.range_len
     }
 }
 
@@ -208,7 +179,7 @@ impl Display for Almanac {
 }
 
 // we expect rule_map to be sorted, so we can implement binary search over it to be a bit faster
-fn map_key_sorted(key: u64, rule_map: &Vec<RangeRule>) -> u64 {
+pub fn map_key_sorted(key: u64, rule_map: &[RangeRule]) -> u64 {
     // we go through rules trying to find it key is in between [src..src+len]
     // if not, we just return as it is
     // we first binary search for rule which captures key
@@ -216,7 +187,7 @@ fn map_key_sorted(key: u64, rule_map: &Vec<RangeRule>) -> u64 {
         if rr.contains(key) {
             Ordering::Equal
         } else {
-            return rr.src_key.cmp(&key);
+            rr.src_key.cmp(&key)
         }
     });
     match found {
@@ -229,7 +200,7 @@ fn map_key_sorted(key: u64, rule_map: &Vec<RangeRule>) -> u64 {
 }
 
 #[allow(dead_code)]
-fn map_key(key: u64, rule_map: &Vec<RangeRule>) -> u64 {
+fn map_key(key: u64, rule_map: &[RangeRule]) -> u64 {
     rule_map
         .iter()
         .find(|rr| rr.contains(key))
@@ -241,7 +212,7 @@ fn map_key(key: u64, rule_map: &Vec<RangeRule>) -> u64 {
         .unwrap_or(key)
 }
 
-fn map_to_location(seed: u64, rule_book: &Vec<Vec<RangeRule>>) -> u64 {
+pub fn map_to_location(seed: u64, rule_book: &[Vec<RangeRule>]) -> u64 {
     rule_book.iter().fold(seed, |key, rules| {
         // eprintln!("Check key {}", key);
         let result = map_key_sorted(key, rules);
@@ -250,45 +221,173 @@ fn map_to_location(seed: u64, rule_book: &Vec<Vec<RangeRule>>) -> u64 {
     })
 }
 
-fn parse_rules(lines: &[String]) -> Vec<RangeRule> {
-    // skip first line with header
-    let mut result: Vec<_> = lines[1..]
-        .iter()
-        .map(|l| l.split_whitespace().map(|s| s.parse::<u64>().unwrap()))
-        .map(|mut nums| {
-            let dst = nums.next().unwrap();
-            let src = nums.next().unwrap();
-            let len = nums.next().unwrap();
-            RangeRule {
-                src_key: src,
-                dst_key: dst,
-                range_len: len,
-            }
+// Rules of the map in the `section`, the problems of its lines go to `problems`
+pub fn parse_rules(section: &Section, problems: &mut Vec<ParseError>) -> Vec<RangeRule> {
+    let header = pair(word, pair(literal("-to-"), pair(word, literal("map:"))));
+    if let Err(e) = section.parse_line(0, header) {
+        problems.push(e);
+    }
+    let rule = map(
+        pair(uint::<u64>, pair(uint::<u64>, uint::<u64>)),
+        |(dst_key, (src_key, range_len))| RangeRule {
+            src_key,
+            dst_key,
+            range_len,
+        },
+    );
+    let mut result: Vec<RangeRule> = (1..section.lines.len())
+        .filter_map(|idx| {
+            section
+                .parse_line(idx, &rule)
+                .map_err(|e| problems.push(e))
+                .ok()
         })
         .collect();
     result.sort_by_key(|rr| rr.src_key);
     result
 }
 
-fn load_input(input_path: &Path) -> io::Result<Almanac> {
-    let file = File::open(input_path)?;
-    let buf_reader = io::BufReader::new(file);
-    let mut lines = buf_reader.lines();
-    let seeds_str = lines.next().unwrap()?;
-    let seeds: Vec<u64> = seeds_str[7..]
-        .split_whitespace()
-        .map(|s| s.parse::<u64>().unwrap())
+const CATEGORIES: [&str; 8] = [
+    "seed",
+    "soil",
+    "fertilizer",
+    "water",
+    "light",
+    "temperature",
+    "humidity",
+    "location",
+];
+
+// Almanac with `size` seed ranges and up to `size` rules in each map.
+// Keys are below 1000 * `size`: the rules of a map cut them into segments
+// and lay the segments out in another order, so the rules never overlap.
+pub fn generate(rng: &mut Rng, size: usize) -> Vec<String> {
+    let key_space = 1000 * size as u64;
+    let seeds: Vec<String> = (0..size)
+        .map(|_| format!("{} {}", rng.range(0..key_space), rng.range(1..1000)))
+        .collect();
+    let mut lines = vec![format!("seeds: {}", seeds.join(" "))];
+    for (from, to) in CATEGORIES.iter().zip(&CATEGORIES[1..]) {
+        lines.push(String::new());
+        lines.push(format!("{}-to-{} map:", from, to));
+        let mut cuts: Vec<u64> = (1..size).map(|_| rng.range(1..key_space)).collect();
+        cuts.extend([0, key_space]);
+        cuts.sort();
+        cuts.dedup();
+        let segments: Vec<Range<u64>> = cuts.windows(2).map(|w| w[0]..w[1]).collect();
+        let mut order: Vec<usize> = (0..segments.len()).collect();
+        rng.shuffle(&mut order);
+        let mut dst_key = 0;
+        let mut rules = Vec::with_capacity(segments.len());
+        for idx in order {
+            let segment = &segments[idx];
+            let len = segment.end - segment.start;
+            rules.push(format!("{} {} {}", dst_key, segment.start, len));
+            dst_key += len;
+        }
+        rng.shuffle(&mut rules);
+        lines.extend(rules);
+    }
+    lines
+}
+
+pub fn check(input_path: &Path) -> Result<Vec<ParseError>> {
+    let lines = input::load(input_path, input::read_lines)?;
+    Ok(parse_lines(&lines).1)
+}
+
+pub fn load_input(input_path: &Path) -> Result<Almanac> {
+    input::load(input_path, parse_input)
+}
+
+pub fn parse_input(reader: impl BufRead) -> Result<Almanac> {
+    let lines = input::read_lines(reader)?;
+    let (almanac, problems) = parse_lines(&lines);
+    match problems.into_iter().next() {
+        Some(e) => Err(e.into()),
+        None => Ok(almanac),
+    }
+}
+
+// Parse the seeds and the maps going on after bad lines, so every problem is found
+fn parse_lines(lines: &[String]) -> (Almanac, Vec<ParseError>) {
+    // the seeds come first, the maps follow in sections of their own
+    let sections = parse::sections(lines);
+    let seeds_section = sections.first().copied().unwrap_or(Section::new(&[]));
+    let (mut seeds, mut problems) = seeds_section.key_value_lines(&["seeds"], list(uint::<u64>));
+    let seeds = seeds.remove(0).unwrap_or_default();
+    // parts 2 to 4 read the seeds as pairs of range start and length
+    let missing = match seeds.len() {
+        0 => Some("seed number"),
+        n if n % 2 == 1 => Some("length of the last seed range"),
+        _ => None,
+    };
+    // a seeds line which does not parse is reported already
+    if let (Some(expected), true) = (missing, problems.is_empty()) {
+        let line = &seeds_section.lines[0];
+        problems.push(ParseError::at_end(line, expected).on_line(seeds_section.first_line));
+    }
+    let rule_book = sections
+        .iter()
+        .skip(1)
+        .map(|section| parse_rules(section, &mut problems))
         .collect();
-    // skip empty line
-    let _ = lines.next().unwrap();
-    // map remaining lines to rulebook
-    let maps_lines: Vec<String> = lines.collect::<io::Result<Vec<String>>>().unwrap();
-    let maps = maps_lines
-        .split(|line| line.is_empty())
-        .map(|lines| parse_rules(lines))
+    let almanac = Almanac { seeds, rule_book };
+    (almanac, problems)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_lines_reports_every_problem() {
+        let lines: Vec<String> = [
+            "seeds: 79 x",
+            "",
+            "seed-to-soil map:",
+            "50 98",
+            "52 50 48",
+            "",
+            "soil to fertilizer map:",
+            "0 15 -37",
+        ]
+        .iter()
+        .map(|l| l.to_string())
         .collect();
-    return Ok(Almanac {
-        seeds,
-        rule_book: maps,
-    });
+        let (_, problems) = parse_lines(&lines);
+        let lines: Vec<_> = problems.iter().map(|e| e.line).collect();
+        assert_eq!(vec![1, 4, 7, 8], lines);
+    }
+
+    #[test]
+    fn test_parse_lines_checks_seed_pairs() {
+        for (seeds, expected) in [
+            ("seeds: 79 14 55", "length of the last seed range"),
+            ("seeds:", "seed number"),
+        ] {
+            let lines: Vec<String> = [seeds, "", "seed-to-soil map:", "50 98 2"]
+                .iter()
+                .map(|l| l.to_string())
+                .collect();
+            let (_, problems) = parse_lines(&lines);
+            assert_eq!(
+                vec![(1, seeds.len() + 1, expected)],
+                problems
+                    .iter()
+                    .map(|e| (e.line, e.column, e.expected.as_str()))
+                    .collect::<Vec<_>>()
+            );
+        }
+    }
+
+    #[test]
+    fn test_parallel_part_agrees() {
+        crate::diff::assert_agree("day5", 2, 3, 30, 8);
+    }
+
+    #[test]
+    fn test_range_part_agrees() {
+        crate::diff::assert_agree("day5", 2, 4, 30, 8);
+    }
 }
@@ -1,79 +1,139 @@
-use std::io;
+use crate::answer::Answer;
+use crate::debug;
+use crate::error::{ParseError, Result};
+use crate::input;
+use crate::parse::{digits, list, uint, Cursor, PResult, Section};
+use crate::rng::Rng;
 use std::io::BufRead;
 use std::iter::zip;
-use std::{fs::File, path::Path};
+use std::path::Path;
 
-pub fn part1(input_path: &Path) {
-    let stats = load_input(input_path).unwrap();
-    eprintln!("Loaded race stats: {:?}", stats);
+pub fn part1(input_path: &Path) -> Result<Answer> {
+    let stats = load_input(input_path)?;
+    debug!("Loaded race stats: {:?}", stats);
     let result: u32 = zip(&stats.times, &stats.distances)
         .map(|(race_time, beat_distance)| {
             winning_races(*race_time as u64, *beat_distance as u64) as u32
         })
         .product();
-    println!("Result: {}", result);
+    Ok(result.into())
 }
 
-pub fn part2(input_path: &Path) {
-    let (time, beat_distance) = load_input_part2(input_path).unwrap();
-    eprintln!("Time: {}, Distance to beat: {}", time, beat_distance);
+pub fn part2(input_path: &Path) -> Result<Answer> {
+    let (time, beat_distance) = load_input_part2(input_path)?;
+    debug!("Time: {}, Distance to beat: {}", time, beat_distance);
     let result = winning_races(time, beat_distance);
-    println!("Result: {}", result);
+    Ok(result.into())
 }
 
 #[derive(Debug)]
-struct RaceStats {
-    times: Vec<u32>,
-    distances: Vec<u32>,
+pub struct RaceStats {
+    pub times: Vec<u32>,
+    pub distances: Vec<u32>,
 }
 
-fn winning_races(time: u64, beat_distance: u64) -> usize {
+// Labels of the two lines of the input
+const KEYS: [&str; 2] = ["Time", "Distance"];
+
+pub fn winning_races(time: u64, beat_distance: u64) -> usize {
     (1..time)
         .map(|hold_time| (time - hold_time) * hold_time)
         .filter(|&distance| distance > beat_distance)
         .count()
 }
 
-fn load_input(input_path: &Path) -> io::Result<RaceStats> {
-    let file = File::open(input_path)?;
-    let buf_reader = io::BufReader::new(file);
-    let mut lines = buf_reader.lines();
-    let times = lines.next().unwrap().map(|l| {
-        l["Time:".len()..]
-            .trim()
-            .split_whitespace()
-            .map(|s| s.parse::<u32>().unwrap())
-            .collect::<Vec<_>>()
-    })?;
-    let distances = lines.next().unwrap().map(|l| {
-        l["Distance:".len()..]
-            .trim()
-            .split_whitespace()
-            .map(|s| s.parse::<u32>().unwrap())
-            .collect::<Vec<_>>()
-    })?;
-    Ok(RaceStats { times, distances })
+// Numbers of the line are a single number with bad kerning
+fn kerned_num(c: Cursor<'_>) -> PResult<'_, u64> {
+    let (parts, rest) = list(digits)(c)?;
+    let num = parts.concat().parse().map_err(|_| c.error("number"))?;
+    Ok((num, rest))
 }
 
-fn load_input_part2(input_path: &Path) -> io::Result<(u64, u64)> {
-    let file = File::open(input_path)?;
-    let buf_reader = io::BufReader::new(file);
-    let mut lines = buf_reader.lines();
-    let time = lines.next().unwrap().map(|l| {
-        l["Time:".len()..]
-            .chars()
-            .filter(|c| c.is_digit(10))
-            .collect::<String>()
-            .parse()
-            .unwrap()
-    })?;
-    let distance = lines.next().unwrap().map(|l| {
-        l["Distance:".len()..]
-            .chars()
-            .filter(|c| c.is_digit(10))
-            .collect::<String>()
-            .parse()
-            .unwrap()
-    })?;
-    Ok((time, distance))
+// `size` races, but at most 4 so the kerned numbers of part 2 fit.
+// Every record can be beaten.
+pub fn generate(rng: &mut Rng, size: usize) -> Vec<String> {
+    let races: Vec<(u64, u64)> = (0..size.min(4))
+        .map(|_| {
+            let time = rng.range(2..100);
+            let best = (time / 2) * (time - time / 2);
+            (time, rng.range(0..best))
+        })
+        .collect();
+    let format_nums =
+        |nums: Vec<u64>| -> String { nums.iter().map(|n| format!("{:>5}", n)).collect() };
+    vec![
+        format!(
+            "Time:    {}",
+            format_nums(races.iter().map(|r| r.0).collect())
+        ),
+        format!(
+            "Distance:{}",
+            format_nums(races.iter().map(|r| r.1).collect())
+        ),
+    ]
+}
+
+pub fn check(input_path: &Path) -> Result<Vec<ParseError>> {
+    let lines = input::load(input_path, input::read_lines)?;
+    Ok(parse_lines(&lines).1)
+}
+
+pub fn load_input(input_path: &Path) -> Result<RaceStats> {
+    input::load(input_path, parse_input)
+}
+
+pub fn parse_input(reader: impl BufRead) -> Result<RaceStats> {
+    let lines = input::read_lines(reader)?;
+    let (stats, problems) = parse_lines(&lines);
+    match problems.into_iter().next() {
+        Some(e) => Err(e.into()),
+        None => Ok(stats),
+    }
+}
+
+// Parse both lines going on after a bad one, so every problem is found
+fn parse_lines(lines: &[String]) -> (RaceStats, Vec<ParseError>) {
+    let (mut nums, mut problems) = Section::new(lines).key_value_lines(&KEYS, list(uint::<u32>));
+    let distances = nums.pop().flatten().unwrap_or_default();
+    let times = nums.pop().flatten().unwrap_or_default();
+    // each time goes with the distance below it, so the shorter line misses some
+    if problems.is_empty() && times.len() != distances.len() {
+        let (idx, len) = if times.len() < distances.len() {
+            (0, distances.len())
+        } else {
+            (1, times.len())
+        };
+        let expected = format!("{} numbers like the {} line", len, KEYS[1 - idx]);
+        problems.push(ParseError::at_end(&lines[idx], expected).on_line(idx + 1));
+    }
+    (RaceStats { times, distances }, problems)
+}
+
+pub fn load_input_part2(input_path: &Path) -> Result<(u64, u64)> {
+    input::load(input_path, parse_input_part2)
+}
+
+pub fn parse_input_part2(reader: impl BufRead) -> Result<(u64, u64)> {
+    let lines = input::read_lines(reader)?;
+    let nums = Section::new(&lines).key_values(&KEYS, kerned_num)?;
+    Ok((nums[0], nums[1]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_lines_reports_missing_races() {
+        let lines: Vec<String> = ["Time: 7 15 30", "Distance: 9 40"]
+            .iter()
+            .map(|l| l.to_string())
+            .collect();
+        let (_, problems) = parse_lines(&lines);
+        let found: Vec<_> = problems
+            .iter()
+            .map(|e| (e.line, e.column, e.expected.as_str()))
+            .collect();
+        assert_eq!(vec![(2, 15, "3 numbers like the Time line")], found);
+    }
 }
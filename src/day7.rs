@@ -1,36 +1,39 @@
+use crate::answer::Answer;
+use crate::error::{parse_num, ParseError, Result};
+use crate::input;
+use crate::rng::Rng;
+use crate::{debug, trace};
 use std::cmp::Ordering;
 use std::collections::HashMap;
 use std::fmt::Display;
-use std::fs::File;
-use std::io::{self, BufRead};
+use std::io::BufRead;
 use std::path::Path;
 
-pub fn part1(input_path: &Path) {
-    let card_map: HashMap<char, u8> =
-        HashMap::from([('T', 10), ('J', 11), ('Q', 12), ('K', 13), ('A', 14)]);
-    let input = load_input(input_path, |c| map_card(&card_map, c)).unwrap();
+pub fn part1(input_path: &Path) -> Result<Answer> {
+    let card_map = card_values(11);
+    let input = load_input(input_path, |c| map_card(&card_map, c))?;
     let result = calc_answer(input);
-    println!("Result: {}", result);
+    Ok(result.into())
 }
 
-pub fn part2(input_path: &Path) {
-    let card_map: HashMap<char, u8> =
-        HashMap::from([('T', 10), ('J', 0), ('Q', 12), ('K', 13), ('A', 14)]);
-    let input = load_input(input_path, |c| map_card(&card_map, c)).unwrap();
+pub fn part2(input_path: &Path) -> Result<Answer> {
+    // J is a joker, the weakest card
+    let card_map = card_values(0);
+    let input = load_input(input_path, |c| map_card(&card_map, c))?;
     let result = calc_answer(input);
-    println!("Result: {}", result);
+    Ok(result.into())
 }
 
-fn calc_answer(input: Vec<Player>) -> u32 {
-    eprintln!("Debug -- Display:");
+pub fn calc_answer(input: Vec<Player>) -> u32 {
+    debug!("Debug -- Display:");
     for hand in &input {
-        eprintln!("{:?} -- {}", hand, hand);
+        debug!("{:?} -- {}", hand, hand);
     }
     let mut players: Vec<Player> = input;
     players.sort_by(|a, b| cmp_players(hand_type, a, b));
-    eprintln!("Sorted hands (ASC):");
+    debug!("Sorted hands (ASC):");
     for h in &players {
-        eprintln!("{} - {:?}", h, hand_type(h.hand));
+        debug!("{} - {:?}", h, hand_type(h.hand));
     }
     players
         .iter()
@@ -39,18 +42,18 @@ fn calc_answer(input: Vec<Player>) -> u32 {
         .sum()
 }
 
-fn cmp_players(hand_type: fn([u8; 5]) -> HandType, lhs: &Player, rhs: &Player) -> Ordering {
+pub fn cmp_players(hand_type: fn([u8; 5]) -> HandType, lhs: &Player, rhs: &Player) -> Ordering {
     // we compare hand types and if they are equal: "lexicographically" compare strings
     let hand_cmp = hand_type(lhs.hand).cmp(&hand_type(rhs.hand));
     if hand_cmp != Ordering::Equal {
-        return hand_cmp;
+        hand_cmp
     } else {
         lhs.hand.cmp(&rhs.hand)
     }
 }
 
 #[derive(PartialEq, Eq, PartialOrd, Ord, Debug)]
-enum HandType {
+pub enum HandType {
     HighCard,
     OnePair,
     TwoPair,
@@ -61,9 +64,9 @@ enum HandType {
 }
 
 #[derive(Debug)]
-struct Player {
-    hand: [u8; 5],
-    bid: u32,
+pub struct Player {
+    pub hand: [u8; 5],
+    pub bid: u32,
 }
 
 fn to_string(hand: &[u8]) -> String {
@@ -92,13 +95,13 @@ impl Display for Player {
     }
 }
 
-fn hand_type(hand: [u8; 5]) -> HandType {
+pub fn hand_type(hand: [u8; 5]) -> HandType {
     let mut card_counts: HashMap<u8, usize> = HashMap::new();
     for c in hand {
         card_counts.entry(c).and_modify(|c| *c += 1).or_insert(1);
     }
 
-    eprintln!("Calc type of hand {}", to_string(&hand));
+    trace!("Calc type of hand {}", to_string(&hand));
     // joker always merges with bigest non-joker card, except the situation where all are jokers
     let &joker_count = card_counts.get(&0).unwrap_or(&0);
     // let's get biggest non-joker card and increase its count by joker count
@@ -112,9 +115,9 @@ fn hand_type(hand: [u8; 5]) -> HandType {
             .filter(|(&k, _)| k != 0)
             .max_by_key(|(_, &count)| count)
             .unwrap();
-        eprint!("{:?}: ", &hand);
-        eprintln!(
-            "Hand {} most freq card: {}",
+        trace!(
+            "{:?}: Hand {} most freq card: {}",
+            &hand,
             to_string(&hand),
             most_freq_card
         );
@@ -124,16 +127,16 @@ fn hand_type(hand: [u8; 5]) -> HandType {
     }
     // remove Joker card
     card_counts.remove(&0);
-    eprintln!("After Joker card counts: {:?}", &card_counts);
+    debug!("After Joker card counts: {:?}", &card_counts);
 
-    return match card_counts.len() {
+    match card_counts.len() {
         1 => HandType::FiveOfKind,
         2 => {
             let random_count = card_counts.values().next().unwrap();
             if *random_count == 1 || *random_count == 4 {
-                return HandType::FourOfKind;
+                HandType::FourOfKind
             } else {
-                return HandType::FullHouse;
+                HandType::FullHouse
             }
         }
         3 => {
@@ -149,52 +152,86 @@ fn hand_type(hand: [u8; 5]) -> HandType {
             "Unexpected hand passed with {} different cards: {:?}",
             count, hand
         ),
-    };
+    }
 }
 
-fn map_card(card_map: &HashMap<char, u8>, c: char) -> u8 {
-    if c.is_digit(10) {
-        c.to_digit(10).unwrap() as u8
-    } else {
-        // eprintln!("map key {} from map {:?}", c, card_map);
-        card_map[&c]
+pub fn map_card(card_map: &HashMap<char, u8>, c: char) -> Option<u8> {
+    match c.to_digit(10) {
+        Some(d) if d >= 2 => Some(d as u8),
+        Some(_) => None,
+        None => card_map.get(&c).copied(),
     }
 }
 
-fn load_input<FM>(input_path: &Path, map_card: FM) -> io::Result<Vec<Player>>
+pub fn parse_player<FM>(line: &str, map_card: FM) -> Result<Player, ParseError>
 where
-    FM: Fn(char) -> u8,
+    FM: Fn(char) -> Option<u8>,
 {
-    let file = File::open(input_path)?;
-    let buf_reader = io::BufReader::new(file);
-    buf_reader
-        .lines()
-        .map(|line| {
-            line.map(|l| {
-                let mut split = l.split_whitespace();
-                let hand = split.next().unwrap().to_string();
-                let bid = split.next().unwrap().parse::<u32>().unwrap();
-                let hand_vec: Vec<u8> = hand.chars().map(|c| map_card(c)).collect();
-                Player {
-                    hand: hand_vec.try_into().unwrap(),
-                    bid: bid,
-                }
-            })
+    let mut split = line.split_whitespace();
+    let hand_str = split
+        .next()
+        .ok_or_else(|| ParseError::at_end(line, "hand"))?;
+    let bid_str = split
+        .next()
+        .ok_or_else(|| ParseError::at_end(line, "bid"))?;
+    let hand_vec = hand_str
+        .char_indices()
+        .map(|(idx, c)| {
+            map_card(c)
+                .ok_or_else(|| ParseError::new(line, &hand_str[idx..idx + c.len_utf8()], "card"))
         })
+        .collect::<Result<Vec<u8>, _>>()?;
+    let hand: [u8; 5] = hand_vec
+        .try_into()
+        .map_err(|_| ParseError::new(line, hand_str, "hand of 5 cards"))?;
+    let bid = parse_num(line, bid_str)?;
+    Ok(Player { hand, bid })
+}
+
+// Values of the face cards, J has the given value
+fn card_values(j_value: u8) -> HashMap<char, u8> {
+    HashMap::from([('T', 10), ('J', j_value), ('Q', 12), ('K', 13), ('A', 14)])
+}
+
+// `size` random hands with bids up to 1000
+pub fn generate(rng: &mut Rng, size: usize) -> Vec<String> {
+    (0..size)
+        .map(|_| format!("{} {}", rng.string("23456789TJQKA", 5), rng.range(1..1001)))
         .collect()
 }
 
+pub fn check(input_path: &Path) -> Result<Vec<ParseError>> {
+    let card_map = card_values(11);
+    input::check_lines(input_path, |line| {
+        parse_player(line, |c| map_card(&card_map, c))
+    })
+}
+
+pub fn load_input<FM>(input_path: &Path, map_card: FM) -> Result<Vec<Player>>
+where
+    FM: Fn(char) -> Option<u8>,
+{
+    input::load(input_path, |reader| parse_input(reader, map_card))
+}
+
+pub fn parse_input<FM>(reader: impl BufRead, map_card: FM) -> Result<Vec<Player>>
+where
+    FM: Fn(char) -> Option<u8>,
+{
+    input::parse_lines(reader, |line| parse_player(line, &map_card))
+}
+
 #[cfg(test)]
 mod tests {
     use std::collections::HashMap;
 
-    use crate::day7::{hand_type, map_card, HandType};
+    use crate::day7::{hand_type, map_card, parse_player, HandType};
 
     fn map_hand<FC>(map_card: FC, s: String) -> [u8; 5]
     where
-        FC: Fn(char) -> u8,
+        FC: Fn(char) -> Option<u8>,
     {
-        let hand_vec: Vec<u8> = s.chars().map(|c| map_card(c)).collect();
+        let hand_vec: Vec<u8> = s.chars().map(|c| map_card(c).unwrap()).collect();
         hand_vec.try_into().unwrap()
     }
 
@@ -225,4 +262,11 @@ mod tests {
         assert_eq!(HandType::FiveOfKind, hand_type(h("JJ2JJ")));
         assert_eq!(HandType::FiveOfKind, hand_type(h("JJJJJ")));
     }
+
+    #[test]
+    fn parse_player_reports_non_ascii_card() {
+        let card_map: HashMap<char, u8> = HashMap::new();
+        let e = parse_player("2é345 10", |c| map_card(&card_map, c)).unwrap_err();
+        assert_eq!(("card", "'é'"), (e.expected.as_str(), e.found.as_str()));
+    }
 }
@@ -1,13 +1,18 @@
+use crate::answer::Answer;
+use crate::cycle::Cycle;
+use crate::error::{ParseError, Result};
+use crate::input;
+use crate::rng::Rng;
+use crate::{debug, trace};
 use num::integer::lcm;
-use std::collections::HashMap;
-use std::fs::File;
-use std::io::{self, BufRead};
+use std::collections::{HashMap, HashSet};
+use std::io::BufRead;
 use std::ops::ControlFlow;
 use std::path::Path;
 use std::str::Chars;
 
-pub fn part1(input_path: &Path) {
-    let map = load_input(input_path).unwrap();
+pub fn part1(input_path: &Path) -> Result<Answer> {
+    let map = load_input(input_path, &PART1_NODES)?;
     // now let's iterate over instructions and jump one by one
     let rep_instr = RepeatedString::new(&map.instructions);
     let result = rep_instr
@@ -26,18 +31,18 @@ pub fn part1(input_path: &Path) {
         });
 
     match result {
-        ControlFlow::Break(steps) => println!("Result: {:}", steps),
+        ControlFlow::Break(steps) => Ok(steps.into()),
         ControlFlow::Continue(_) => unreachable!(),
     }
 }
 
-pub fn part2(input_path: &Path) {
-    let map = load_input(input_path).unwrap();
+pub fn part2(input_path: &Path) -> Result<Answer> {
+    let map = load_input(input_path, &[])?;
     // now let's iterate over instructions and jump one by one
     let rep_instr = RepeatedString::new(&map.instructions);
     // now the state is now just single node but list of nodes
     let start_nodes: Vec<&String> = map.network.keys().filter(|n| n.ends_with("A")).collect();
-    eprintln!("Starting nodes: {:?}", start_nodes);
+    debug!("Starting nodes: {:?}", start_nodes);
     // another possibility: let's count how many steps from each starting point until Z
     // and then find least common multiple
     let steps_by_node: Vec<u64> = start_nodes
@@ -61,7 +66,7 @@ pub fn part2(input_path: &Path) {
                     });
             match result {
                 ControlFlow::Break((steps, node)) => {
-                    eprintln!("{} reached {} in {} steps", &start_node, node, steps);
+                    trace!("{} reached {} in {} steps", &start_node, node, steps);
                     steps as u64
                 }
                 ControlFlow::Continue(_) => unreachable!(),
@@ -69,52 +74,59 @@ pub fn part2(input_path: &Path) {
         })
         .collect();
     let result = steps_by_node.iter().fold(1u64, |curr_lcm, val| {
-        eprintln!("Calculate LCM of {} and {}", curr_lcm, val);
+        trace!("Calculate LCM of {} and {}", curr_lcm, val);
         lcm(curr_lcm, *val)
     });
-    println!("Result: {}", result);
+    Ok(result.into())
 }
 
-// Test searching for a loop
-pub fn part3(input_path: &Path) {
-    let map = load_input(input_path).unwrap();
-    // let's go through a loop and check how often we will encounter Z
-    let start_nodes: Vec<&String> = map.network.keys().filter(|n| n.ends_with("A")).collect();
-    eprintln!("Start nodes: {:?}", start_nodes);
+// Z nodes hit by each ghost until its walk repeats: the walk state is the node with
+// the position in the instructions, so it loops within nodes * instructions steps
+pub fn part3(input_path: &Path) -> Result<Answer> {
+    let map = load_input(input_path, &[])?;
+    let mut start_nodes: Vec<&String> = map.network.keys().filter(|n| n.ends_with("A")).collect();
+    // keep the order stable so the answer is the same between runs
+    start_nodes.sort();
+    debug!("Start nodes: {:?}", start_nodes);
+    let instructions = map.instructions.as_bytes();
+    let mut z_hits = Vec::new();
     for start_node in start_nodes {
-        eprintln!("Check start node: {}", start_node);
-
-        let rep_instr = RepeatedString::new(&map.instructions);
-        rep_instr
-            .take(50)
-            .enumerate()
-            .fold(start_node, |n: &String, (step, direction)| {
-                if n.ends_with("Z") {
-                    println!("Step: {}, node: {}", step, n);
-                }
-                let (next_l, next_r) = &map.network[n];
-                match direction {
-                    'L' => next_l,
-                    'R' => next_r,
-                    _ => unreachable!(),
-                }
-            });
+        let cycle = Cycle::find((start_node.as_str(), 0), |&(node, idx)| {
+            let (next_l, next_r) = &map.network[node];
+            let next = match instructions[idx] {
+                b'L' => next_l,
+                b'R' => next_r,
+                _ => unreachable!(),
+            };
+            (next.as_str(), (idx + 1) % instructions.len())
+        });
+        trace!(
+            "{} loops every {} steps from step {}",
+            start_node,
+            cycle.period,
+            cycle.start
+        );
+        for (step, (node, _)) in cycle.states().iter().enumerate() {
+            if node.ends_with("Z") {
+                z_hits.push(format!("{}@{}", node, step));
+            }
+        }
     }
-    // the idea would be for each starting position to find its "cycle" length and then find multiple
+    Ok(z_hits.join(" ").into())
 }
 
 // Iterator which emits characters from string, repeated from the beginning when string ends
-struct RepeatedString<'a> {
-    string: &'a str,
-    current_iter: Chars<'a>,
+pub struct RepeatedString<'a> {
+    pub string: &'a str,
+    pub current_iter: Chars<'a>,
 }
 
 impl RepeatedString<'_> {
     fn new<'a>(string: &'a str) -> RepeatedString<'a> {
         let current_iter = string.chars();
         RepeatedString {
-            string: string,
-            current_iter: current_iter,
+            string,
+            current_iter,
         }
     }
 }
@@ -140,34 +152,172 @@ impl<'a> Clone for RepeatedString<'a> {
 }
 
 #[derive(Debug)]
-struct DesertMap {
-    instructions: String,
-    network: HashMap<String, (String, String)>,
-}
-
-fn load_input(input_path: &Path) -> io::Result<DesertMap> {
-    let file = File::open(input_path)?;
-    let buf_reader = io::BufReader::new(file);
-    let mut lines = buf_reader.lines();
-    let instructions = lines.next().unwrap()?;
-    let _ = lines.next().unwrap()?; // skip empty line
-    let network_res: io::Result<HashMap<_, _>> = lines
-        .map(|line| {
-            line.map(|l|
-        // network nodes are always 3-letter
-        (l[0..3].to_string(), (l[7..10].to_string(), l[12..15].to_string())))
-        })
-        .collect();
-    let network = network_res?;
-    Ok(DesertMap {
+pub struct DesertMap {
+    pub instructions: String,
+    pub network: HashMap<String, (String, String)>,
+}
+
+fn parse_instructions(line: &str) -> Result<String, ParseError> {
+    if line.is_empty() {
+        return Err(ParseError::at_end(line, "'L' or 'R'"));
+    }
+    if let Some((idx, c)) = line.char_indices().find(|(_, c)| *c != 'L' && *c != 'R') {
+        return Err(ParseError::new(
+            line,
+            &line[idx..idx + c.len_utf8()],
+            "'L' or 'R'",
+        ));
+    }
+    Ok(line.to_string())
+}
+
+pub fn parse_node(line: &str) -> Result<(String, (String, String)), ParseError> {
+    // network nodes are always 3-letter: "AAA = (BBB, CCC)"
+    let rest = |at: usize| line.get(at..).unwrap_or(&line[line.len()..]);
+    let name = |at: usize| -> Result<String, ParseError> {
+        match line.get(at..at + 3) {
+            Some(s) if s.chars().all(|c| c.is_ascii_alphanumeric()) => Ok(s.to_string()),
+            _ => Err(ParseError::new(line, rest(at), "3-letter node name")),
+        }
+    };
+    let literal = |at: usize, lit: &str| -> Result<(), ParseError> {
+        if line.get(at..at + lit.len()) == Some(lit) {
+            Ok(())
+        } else {
+            Err(ParseError::new(line, rest(at), format!("'{}'", lit)))
+        }
+    };
+    let node = name(0)?;
+    literal(3, " = (")?;
+    let left = name(7)?;
+    literal(10, ", ")?;
+    let right = name(12)?;
+    literal(15, ")")?;
+    Ok((node, (left, right)))
+}
+
+// Network of about `size` nodes, at most 10000, walked by up to 4 ghosts.
+// Each ghost walks from its ..A node through a chain of nodes to its ..Z node,
+// which leads back to the start of the chain, so the ghost is on Z every
+// chain length steps like in the puzzle. The first ghost walks from AAA to ZZZ.
+// Both branches of a node lead to the same node, so the instructions only add noise.
+pub fn generate(rng: &mut Rng, size: usize) -> Vec<String> {
+    const LETTERS: &str = "ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+    let size = size.min(10_000);
+    let ghosts = (1 + size / 50).min(4);
+    let mut used: HashSet<String> = HashSet::from(["AAA".to_string(), "ZZZ".to_string()]);
+    // unique name ending with one of the `last` letters
+    let mut name = |rng: &mut Rng, last: &str| loop {
+        let name = rng.string(LETTERS, 2) + &rng.string(last, 1);
+        if used.insert(name.clone()) {
+            return name;
+        }
+    };
+    let instructions_len = rng.range(1..size.min(300) as u64 + 1) as usize;
+    let mut nodes = Vec::with_capacity(size + 2 * ghosts);
+    for ghost in 0..ghosts {
+        let (start, end) = if ghost == 0 {
+            ("AAA".to_string(), "ZZZ".to_string())
+        } else {
+            (name(rng, "A"), name(rng, "Z"))
+        };
+        let chain_len = rng.range(1..(size / ghosts).max(1) as u64 + 1);
+        let chain: Vec<String> = (0..chain_len)
+            .map(|_| name(rng, "BCDEFGHIJKLMNOPQRSTUVWXY"))
+            .collect();
+        let mut path = vec![start];
+        path.extend(chain.iter().cloned());
+        path.push(end);
+        for (node, next) in path.iter().zip(path.iter().skip(1).chain([&chain[0]])) {
+            nodes.push(format!("{} = ({}, {})", node, next, next));
+        }
+    }
+    rng.shuffle(&mut nodes);
+    let mut lines = vec![rng.string("LR", instructions_len), String::new()];
+    lines.extend(nodes);
+    lines
+}
+
+// Nodes part 1 walks between. The ghosts of parts 2 and 3 start from any ..A node,
+// so their examples do without these.
+const PART1_NODES: [&str; 2] = ["AAA", "ZZZ"];
+
+// A puzzle input is used for every part, so it needs the nodes of part 1 as well
+pub fn check(input_path: &Path) -> Result<Vec<ParseError>> {
+    let lines = input::load(input_path, input::read_lines)?;
+    Ok(parse_lines(&lines, &PART1_NODES).1)
+}
+
+// Load the map, which should define the `required` nodes
+pub fn load_input(input_path: &Path, required: &[&str]) -> Result<DesertMap> {
+    input::load(input_path, |reader| parse_input(reader, required))
+}
+
+pub fn parse_input(reader: impl BufRead, required: &[&str]) -> Result<DesertMap> {
+    let lines = input::read_lines(reader)?;
+    let (map, problems) = parse_lines(&lines, required);
+    match problems.into_iter().next() {
+        Some(e) => Err(e.into()),
+        None => Ok(map),
+    }
+}
+
+// Parse the instructions and the nodes going on after bad lines, so every problem is found:
+// lines which do not parse, references to undefined nodes, no ..A node to start from
+// and missing `required` nodes
+fn parse_lines(lines: &[String], required: &[&str]) -> (DesertMap, Vec<ParseError>) {
+    let mut problems = Vec::new();
+    let first_line = lines.first().map(|l| l.as_str()).unwrap_or("");
+    let instructions = parse_instructions(first_line).unwrap_or_else(|e| {
+        problems.push(e.on_line(1));
+        String::new()
+    });
+    // skip empty line
+    if let Some(line) = lines.get(1).filter(|l| !l.is_empty()) {
+        problems.push(ParseError::new(line, line, "empty line").on_line(2));
+    }
+    let mut network = HashMap::with_capacity(lines.len());
+    // (line index, target) of both branches of every node
+    let mut references = Vec::new();
+    for (idx, line) in lines.iter().enumerate().skip(2) {
+        match parse_node(line) {
+            Ok((node, next)) => {
+                references.extend([(idx, &line[7..10]), (idx, &line[12..15])]);
+                network.insert(node, next);
+            }
+            Err(e) => problems.push(e.on_line(idx + 1)),
+        }
+    }
+    for (idx, target) in references {
+        if !network.contains_key(target) {
+            let line = &lines[idx];
+            problems.push(ParseError::new(line, target, "defined node").on_line(idx + 1));
+        }
+    }
+    // the missing nodes are reported after the last line, where they could be added
+    let last_line = lines.last().map(|l| l.as_str()).unwrap_or("");
+    let mut missing = |expected: String| {
+        problems.push(ParseError::at_end(last_line, expected).on_line(lines.len().max(1)));
+    };
+    if !network.keys().any(|node| node.ends_with('A')) {
+        missing("node ending with 'A'".to_string());
+    }
+    for node in required {
+        if !network.contains_key(*node) {
+            missing(format!("node '{}'", node));
+        }
+    }
+    problems.sort_by_key(|e| (e.line, e.column));
+    let map = DesertMap {
         instructions,
         network,
-    })
+    };
+    (map, problems)
 }
 
 #[cfg(test)]
 mod tests {
-    use super::RepeatedString;
+    use super::{parse_instructions, parse_lines, RepeatedString, PART1_NODES};
 
     #[test]
     fn repeated_string_repeats() {
@@ -176,4 +326,55 @@ mod tests {
         let result: String = rep_str.take(8).collect();
         assert_eq!("LLRLLRLL", result)
     }
+
+    #[test]
+    fn parse_instructions_reports_non_ascii() {
+        let e = parse_instructions("LRéL").unwrap_err();
+        assert_eq!(("'é'", 3), (e.found.as_str(), e.column));
+    }
+
+    #[test]
+    fn parse_lines_reports_every_problem() {
+        let lines: Vec<String> = ["LR", "", "AAA = (BBB, ZZZ)", "BBB = BBB", "ZZZ = (ZZ)"]
+            .iter()
+            .map(|l| l.to_string())
+            .collect();
+        let (_, problems) = parse_lines(&lines, &[]);
+        let found: Vec<_> = problems
+            .iter()
+            .map(|e| (e.line, e.expected.as_str(), e.found.as_str()))
+            .collect();
+        assert_eq!(
+            vec![
+                (3, "defined node", "'BBB'"),
+                (3, "defined node", "'ZZZ'"),
+                (4, "' = ('", "' = BBB'"),
+                (5, "3-letter node name", "'ZZ)'"),
+            ],
+            found
+        );
+    }
+
+    #[test]
+    fn parse_lines_reports_missing_nodes() {
+        let lines: Vec<String> = ["LR", "", "BBB = (CCC, CCC)", "CCC = (BBB, BBB)"]
+            .iter()
+            .map(|l| l.to_string())
+            .collect();
+        let (_, problems) = parse_lines(&lines, &PART1_NODES);
+        let found: Vec<_> = problems
+            .iter()
+            .map(|e| (e.line, e.expected.as_str()))
+            .collect();
+        assert_eq!(
+            vec![
+                (4, "node ending with 'A'"),
+                (4, "node 'AAA'"),
+                (4, "node 'ZZZ'")
+            ],
+            found
+        );
+        let (_, problems) = parse_lines(&lines, &[]);
+        assert_eq!(1, problems.len());
+    }
 }
@@ -1,41 +1,45 @@
-use std::fs::File;
-use std::io::{self, BufRead};
+use crate::answer::Answer;
+use crate::error::{parse_num, ParseError, Result};
+use crate::input;
+use crate::rng::Rng;
+use crate::{debug, trace};
+use std::io::BufRead;
 use std::path::Path;
 
-pub fn part1(input_path: &Path) {
-    let input = load_input(input_path).unwrap();
+pub fn part1(input_path: &Path) -> Result<Answer> {
+    let input = load_input(input_path)?;
     for row in &input {
-        eprintln!("{:?}", row);
+        debug!("{:?}", row);
     }
-    eprintln!("Run simulation");
+    debug!("Run simulation");
     let result: i32 = input.iter().map(|row| row_prediction(row)).sum();
-    println!("Result: {}", result);
+    Ok(result.into())
 }
 
-pub fn part2(input_path: &Path) {
-    let input = load_input(input_path).unwrap();
+pub fn part2(input_path: &Path) -> Result<Answer> {
+    let input = load_input(input_path)?;
     for row in &input {
-        eprintln!("{:?}", row);
+        debug!("{:?}", row);
     }
-    eprintln!("Run simulation");
+    debug!("Run simulation");
     let result: i32 = input
         .iter()
         .map(|row| {
-            let rev_row: Vec<i32> = row.iter().rev().map(|n| *n).collect();
+            let rev_row: Vec<i32> = row.iter().rev().copied().collect();
             row_prediction(&rev_row)
         })
         .sum();
-    println!("Result: {}", result);
+    Ok(result.into())
 }
 
-fn row_prediction(row: &Vec<i32>) -> i32 {
-    let mut collected_diffs: Vec<Vec<i32>> = vec![row.clone()];
+pub fn row_prediction(row: &[i32]) -> i32 {
+    let mut collected_diffs: Vec<Vec<i32>> = vec![row.to_vec()];
     let mut cur_row = row;
     loop {
         let diffs: Vec<i32> = cur_row.windows(2).map(|w| w[1] - w[0]).collect();
-        eprintln!("Diffs: {:?}", diffs);
+        trace!("Diffs: {:?}", diffs);
         if diffs.iter().all(|d| *d == 0) {
-            eprintln!("Reached zero diffs!");
+            trace!("Reached zero diffs!");
             break;
         } else {
             collected_diffs.push(diffs);
@@ -43,7 +47,7 @@ fn row_prediction(row: &Vec<i32>) -> i32 {
         }
     }
     // now we go back: we get last value from next vector and add it to the last value of our vector
-    eprintln!("{:?}", &collected_diffs);
+    trace!("{:?}", &collected_diffs);
     // predictions go from last to first
     let mut row_predictions: Vec<i32> = vec![0];
     // let's start with last value
@@ -51,21 +55,39 @@ fn row_prediction(row: &Vec<i32>) -> i32 {
     for row in collected_diffs.iter().rev() {
         row_predictions.push(row.last().unwrap() + row_predictions.last().unwrap());
     }
-    eprintln!("Predictions: {:?}", row_predictions);
+    trace!("Predictions: {:?}", row_predictions);
     *row_predictions.last().unwrap()
 }
 
-fn load_input(input_path: &Path) -> io::Result<Vec<Vec<i32>>> {
-    let file = File::open(input_path)?;
-    let buf_reader = io::BufReader::new(file);
-    let lines = buf_reader.lines();
-    lines
-        .map(|line| {
-            line.map(|l| {
-                l.split_whitespace()
-                    .map(|nstr| nstr.parse().unwrap())
-                    .collect()
-            })
+// `size` sequences of values of polynomials of degree up to 3 with small coefficients,
+// each long enough for its differences to reach zeros
+pub fn generate(rng: &mut Rng, size: usize) -> Vec<String> {
+    (0..size)
+        .map(|_| {
+            let coefs: Vec<i64> = (0..rng.range(1..5)).map(|_| rng.range_i64(-5..6)).collect();
+            let len = rng.range(coefs.len() as u64 + 3..22) as i64;
+            (0..len)
+                .map(|x| coefs.iter().rev().fold(0, |acc, c| acc * x + c).to_string())
+                .collect::<Vec<_>>()
+                .join(" ")
         })
         .collect()
 }
+
+pub fn check(input_path: &Path) -> Result<Vec<ParseError>> {
+    input::check_lines(input_path, parse_line)
+}
+
+pub fn load_input(input_path: &Path) -> Result<Vec<Vec<i32>>> {
+    input::load(input_path, parse_input)
+}
+
+pub fn parse_input(reader: impl BufRead) -> Result<Vec<Vec<i32>>> {
+    input::parse_lines(reader, parse_line)
+}
+
+fn parse_line(line: &str) -> Result<Vec<i32>, ParseError> {
+    line.split_whitespace()
+        .map(|nstr| parse_num(line, nstr))
+        .collect()
+}
@@ -0,0 +1,273 @@
+use crate::error::Result;
+use crate::input;
+use crate::rng::Rng;
+use crate::runner::{self, Day, Part, PartFn, PartKind, PartRun};
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+// Differential testing: two parts of a day which should give the same answers
+// run on the same inputs, and an input on which they disagree is shrunk
+// to the smallest one which still shows the disagreement.
+pub struct Comparison<'a> {
+    pub day: &'static str,
+    pub entry: &'a Day,
+    // 1-based part numbers
+    pub parts: (usize, usize),
+    pub timeout: Option<Duration>,
+}
+
+pub struct Disagreement {
+    // the smallest input found and what both parts give on it
+    pub lines: Vec<String>,
+    pub outcomes: (String, String),
+    // lines of the input before it was shrunk
+    pub original_len: usize,
+    // seed and size of the generated input, None for an input file
+    pub generated: Option<(u64, usize)>,
+}
+
+// What a run gives to compare: its answer, or its status when there is none
+fn outcome(run: &PartRun) -> String {
+    match &run.answer {
+        Some(answer) => answer.to_string(),
+        None => run.status.to_string(),
+    }
+}
+
+// Input file in the temp dir, removed when dropped
+struct TempInput(PathBuf);
+
+impl TempInput {
+    fn new(lines: &[String]) -> io::Result<TempInput> {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        let name = format!(
+            "rust-aoc-diff-{}-{}.txt",
+            process::id(),
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        );
+        let path = env::temp_dir().join(name);
+        fs::write(&path, lines.join("\n") + "\n")?;
+        Ok(TempInput(path))
+    }
+}
+
+impl Drop for TempInput {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.0);
+    }
+}
+
+impl Comparison<'_> {
+    pub fn run(&self, path: &Path) -> (String, String) {
+        let run = |part: usize| {
+            let part_fn = self.entry.parts[part - 1].run;
+            outcome(&runner::run_part_with_timeout(
+                self.day,
+                part,
+                part_fn,
+                path,
+                self.timeout,
+            ))
+        };
+        (run(self.parts.0), run(self.parts.1))
+    }
+
+    // Outcomes of the parts when they disagree on the input.
+    // Inputs which do not follow the grammar of the day do not count.
+    fn disagreement(&self, lines: &[String]) -> Result<Option<(String, String)>> {
+        let input = TempInput::new(lines)?;
+        if !(self.entry.check)(&input.0)?.is_empty() {
+            return Ok(None);
+        }
+        let outcomes = self.run(&input.0);
+        Ok((outcomes.0 != outcomes.1).then_some(outcomes))
+    }
+
+    // Compare the parts on the input file, a disagreement is shrunk
+    pub fn compare_file(&self, path: &Path) -> Result<Option<Disagreement>> {
+        let outcomes = self.run(path);
+        if outcomes.0 == outcomes.1 {
+            return Ok(None);
+        }
+        let lines = input::load(path, input::read_lines)?;
+        self.shrink(lines, outcomes, None).map(Some)
+    }
+
+    // Compare the parts on `count` generated inputs with sizes growing up to `max_size`,
+    // the i-th generated from `seed + i`. The first disagreement is shrunk.
+    pub fn search(&self, seed: u64, count: usize, max_size: usize) -> Result<Option<Disagreement>> {
+        for idx in 0..count {
+            let size = 1 + idx * max_size / count;
+            let seed = seed.wrapping_add(idx as u64);
+            let lines = (self.entry.generate)(&mut Rng::new(seed), size);
+            if let Some(outcomes) = self.disagreement(&lines)? {
+                return self.shrink(lines, outcomes, Some((seed, size))).map(Some);
+            }
+        }
+        Ok(None)
+    }
+
+    // Remove chunks of lines, halving the chunks down to single lines,
+    // as long as the input stays valid and the parts still disagree on it
+    fn shrink(
+        &self,
+        mut lines: Vec<String>,
+        mut outcomes: (String, String),
+        generated: Option<(u64, usize)>,
+    ) -> Result<Disagreement> {
+        let original_len = lines.len();
+        let mut chunk = (lines.len() / 2).max(1);
+        loop {
+            let mut removed = false;
+            let mut start = 0;
+            while start < lines.len() {
+                let end = (start + chunk).min(lines.len());
+                let mut candidate = lines.clone();
+                candidate.drain(start..end);
+                match self.disagreement(&candidate)? {
+                    Some(found) => {
+                        lines = candidate;
+                        outcomes = found;
+                        removed = true;
+                    }
+                    None => start = end,
+                }
+            }
+            if chunk == 1 && !removed {
+                break;
+            }
+            chunk = (chunk / 2).max(1);
+        }
+        Ok(Disagreement {
+            lines,
+            outcomes,
+            original_len,
+            generated,
+        })
+    }
+
+    pub fn report(&self, disagreement: &Disagreement) -> String {
+        let source = match disagreement.generated {
+            Some((seed, size)) => {
+                format!("the input generated from seed {} with size {}", seed, size)
+            }
+            None => "the input".to_string(),
+        };
+        let mut result = format!(
+            "{} part{} and part{} disagree on {}, shrunk from {} to {} lines:\n",
+            self.day,
+            self.parts.0,
+            self.parts.1,
+            source,
+            disagreement.original_len,
+            disagreement.lines.len()
+        );
+        for line in &disagreement.lines {
+            result.push_str(&format!("    {}\n", line));
+        }
+        result.push_str(&format!(
+            "part{}: {}\npart{}: {}",
+            self.parts.0, disagreement.outcomes.0, self.parts.1, disagreement.outcomes.1
+        ));
+        result
+    }
+}
+
+// Test helper: panic with the smallest input found on which the parts of the day
+// disagree, comparing them on `count` generated inputs of size up to `max_size`
+pub fn assert_agree(day: &str, part_a: usize, part_b: usize, count: usize, max_size: usize) {
+    let days = crate::days();
+    let (&day, entry) = days
+        .get_key_value(day)
+        .unwrap_or_else(|| panic!("Unknown day {}", day));
+    assert_no_disagreement(day, entry, (part_a, part_b), count, max_size);
+}
+
+// Test helper like `assert_agree` for a part of the day and a function which is not
+// registered, such as another way to solve the part kept only for the tests.
+// The function is reported as the part after the last registered one.
+pub fn assert_agrees_with(day: &str, part: usize, other: PartFn, count: usize, max_size: usize) {
+    let mut days = crate::days();
+    let (day, mut entry) = days
+        .remove_entry(day)
+        .unwrap_or_else(|| panic!("Unknown day {}", day));
+    entry.parts.push(Part {
+        run: other,
+        kind: PartKind::Experimental,
+        description: "not registered",
+    });
+    let other_part = entry.parts.len();
+    assert_no_disagreement(day, &entry, (part, other_part), count, max_size);
+}
+
+fn assert_no_disagreement(
+    day: &'static str,
+    entry: &Day,
+    parts: (usize, usize),
+    count: usize,
+    max_size: usize,
+) {
+    let comparison = Comparison {
+        day,
+        entry,
+        parts,
+        timeout: None,
+    };
+    match comparison.search(1, count, max_size) {
+        Ok(None) => {}
+        Ok(Some(disagreement)) => panic!("{}", comparison.report(&disagreement)),
+        Err(e) => panic!("{}", e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::answer::Answer;
+
+    fn count_lines(path: &Path) -> Result<Answer> {
+        Ok(input::load(path, input::read_lines)?.len().into())
+    }
+
+    // wrong as soon as the input has a "bad" line
+    fn count_good_lines(path: &Path) -> Result<Answer> {
+        let lines = input::load(path, input::read_lines)?;
+        Ok(lines.iter().filter(|l| *l != "bad").count().into())
+    }
+
+    fn generate(rng: &mut Rng, size: usize) -> Vec<String> {
+        (0..size)
+            .map(|_| if rng.chance(0.2) { "bad" } else { "good" }.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_search_shrinks_disagreement() {
+        let part = |run| Part {
+            run,
+            kind: PartKind::Official,
+            description: "",
+        };
+        let entry = Day {
+            title: "Test",
+            check: |_| Ok(Vec::new()),
+            generate,
+            parts: vec![part(count_lines), part(count_good_lines), part(count_lines)],
+        };
+        let comparison = |parts| Comparison {
+            day: "day0",
+            entry: &entry,
+            parts,
+            timeout: None,
+        };
+        assert!(comparison((1, 3)).search(1, 20, 10).unwrap().is_none());
+        let disagreement = comparison((1, 2)).search(1, 20, 10).unwrap().unwrap();
+        assert_eq!(vec!["bad".to_string()], disagreement.lines);
+        assert_eq!(("1".to_string(), "0".to_string()), disagreement.outcomes);
+    }
+}
@@ -0,0 +1,186 @@
+use std::fmt::Display;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+// Crate-wide error of loading and parsing puzzle inputs
+#[derive(Debug)]
+pub enum Error {
+    Io(io::Error),
+    Parse(ParseError),
+}
+
+// Input which does not match the grammar of a day.
+// Line and column are 1-based, line is 0 while the error is not attached to a line yet.
+// The column counts chars, so it matches what an editor shows for non-ASCII lines.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub file: Option<PathBuf>,
+    pub line: usize,
+    pub column: usize,
+    pub expected: String,
+    pub found: String,
+    pub text: String,
+}
+
+impl ParseError {
+    // Error about `found`, which should be a slice of `text`: its position gives the column
+    pub fn new(text: &str, found: &str, expected: impl Into<String>) -> Self {
+        let offset = (found.as_ptr() as usize).wrapping_sub(text.as_ptr() as usize);
+        let column = match text.get(..offset) {
+            Some(before) => before.chars().count() + 1,
+            None => 1,
+        };
+        let found = if found.is_empty() {
+            "end of line".to_string()
+        } else {
+            format!("'{}'", found)
+        };
+        ParseError {
+            file: None,
+            line: 0,
+            column,
+            expected: expected.into(),
+            found,
+            text: text.to_string(),
+        }
+    }
+
+    // Error about missing token at the end of `text`
+    pub fn at_end(text: &str, expected: impl Into<String>) -> Self {
+        Self::new(text, &text[text.len()..], expected)
+    }
+
+    pub fn on_line(self, line: usize) -> Self {
+        ParseError { line, ..self }
+    }
+
+    pub fn in_file(self, path: &Path) -> Self {
+        ParseError {
+            file: Some(display_path(path)),
+            ..self
+        }
+    }
+}
+
+impl Error {
+    // Attach the input file to errors which do not know it yet
+    pub fn in_file(self, path: &Path) -> Self {
+        match self {
+            Error::Parse(e) if e.file.is_none() => Error::Parse(e.in_file(path)),
+            Error::Io(e) => Error::Io(io::Error::new(
+                e.kind(),
+                format!("{}: {}", display_path(path).display(), e),
+            )),
+            e => e,
+        }
+    }
+}
+
+// "-" on the command line means stdin, which is how rustc names it in diagnostics
+fn display_path(path: &Path) -> PathBuf {
+    if path == Path::new("-") {
+        PathBuf::from("<stdin>")
+    } else {
+        path.to_path_buf()
+    }
+}
+
+// Parse `s`, which should be a slice of `text`, as a number
+pub fn parse_num<T: FromStr>(text: &str, s: &str) -> Result<T, ParseError> {
+    s.parse().map_err(|_| ParseError::new(text, s, "number"))
+}
+
+// Position of the first `pat` in `s`, which should be a slice of `text`
+pub fn find(text: &str, s: &str, pat: char) -> Result<usize, ParseError> {
+    s.find(pat)
+        .ok_or_else(|| ParseError::new(text, &s[s.len()..], format!("'{}'", pat)))
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // mimic rustc diagnostics:
+        // error: expected number, found 'x'
+        //  --> day21.test:1:6
+        //   |
+        // 1 | Game x: 3 blue
+        //   |      ^
+        writeln!(f, "error: expected {}, found {}", self.expected, self.found)?;
+        let file = self
+            .file
+            .as_ref()
+            .map(|p| p.display().to_string())
+            .unwrap_or_else(|| "<input>".to_string());
+        let line_no = self.line.to_string();
+        let margin = " ".repeat(line_no.len());
+        writeln!(f, "{}--> {}:{}:{}", margin, file, self.line, self.column)?;
+        writeln!(f, "{} |", margin)?;
+        writeln!(f, "{} | {}", line_no, self.text)?;
+        write!(f, "{} | {}^", margin, " ".repeat(self.column - 1))
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Io(e) => write!(f, "error: {}", e),
+            Error::Parse(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Error::Io(e)
+    }
+}
+
+impl From<ParseError> for Error {
+    fn from(e: ParseError) -> Self {
+        Error::Parse(e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_column_from_slice() {
+        let text = "Game x: 3 blue";
+        let e = ParseError::new(text, &text[5..6], "number");
+        assert_eq!(6, e.column);
+        assert_eq!("'x'", e.found);
+        let e = ParseError::at_end(text, "';'");
+        assert_eq!(text.len() + 1, e.column);
+        assert_eq!("end of line", e.found);
+    }
+
+    #[test]
+    fn test_display() {
+        let text = "Game x: 3 blue";
+        let e = parse_num::<u32>(text, &text[5..6])
+            .unwrap_err()
+            .on_line(1)
+            .in_file(Path::new("day21.test"));
+        let expected = "error: expected number, found 'x'\n \
+                        --> day21.test:1:6\n  |\n\
+                        1 | Game x: 3 blue\n  |      ^";
+        assert_eq!(expected, e.to_string());
+    }
+
+    #[test]
+    fn test_display_non_ascii() {
+        let text = "Café x: 3";
+        let e = parse_num::<u32>(text, &text[6..7]).unwrap_err().on_line(2);
+        assert_eq!(6, e.column);
+        let expected = "error: expected number, found 'x'\n \
+                        --> <input>:2:6\n  |\n\
+                        2 | Café x: 3\n  |      ^";
+        assert_eq!(expected, e.to_string());
+    }
+}
@@ -0,0 +1,164 @@
+// Positions and directions on a grid: rows grow to the south, columns to the east
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Point {
+    pub row: usize,
+    pub col: usize,
+}
+
+// Rectangle of the rows 0..height and the columns 0..width
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Bounds {
+    pub height: usize,
+    pub width: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Direction {
+    North,
+    East,
+    South,
+    West,
+}
+
+impl Point {
+    pub fn new(row: usize, col: usize) -> Point {
+        Point { row, col }
+    }
+
+    // Next point in the direction, None if it leaves the bounds
+    pub fn step(self, dir: Direction, bounds: Bounds) -> Option<Point> {
+        self.step_by(dir, 1, bounds)
+    }
+
+    // Point `n` steps away in the direction, None if it leaves the bounds
+    pub fn step_by(self, dir: Direction, n: usize, bounds: Bounds) -> Option<Point> {
+        let (d_row, d_col) = dir.delta();
+        let row = self
+            .row
+            .checked_add_signed(d_row.checked_mul(n as isize)?)?;
+        let col = self
+            .col
+            .checked_add_signed(d_col.checked_mul(n as isize)?)?;
+        let next = Point::new(row, col);
+        bounds.contains(next).then_some(next)
+    }
+}
+
+impl From<(usize, usize)> for Point {
+    fn from((row, col): (usize, usize)) -> Point {
+        Point::new(row, col)
+    }
+}
+
+impl From<Point> for (usize, usize) {
+    fn from(p: Point) -> (usize, usize) {
+        (p.row, p.col)
+    }
+}
+
+impl Bounds {
+    pub fn new(height: usize, width: usize) -> Bounds {
+        Bounds { height, width }
+    }
+
+    pub fn contains(&self, p: Point) -> bool {
+        p.row < self.height && p.col < self.width
+    }
+}
+
+impl Direction {
+    // Clockwise from north
+    pub const ALL: [Direction; 4] = [
+        Direction::North,
+        Direction::East,
+        Direction::South,
+        Direction::West,
+    ];
+
+    // (row, col) change of one step
+    pub fn delta(self) -> (isize, isize) {
+        match self {
+            Direction::North => (-1, 0),
+            Direction::East => (0, 1),
+            Direction::South => (1, 0),
+            Direction::West => (0, -1),
+        }
+    }
+
+    pub fn turn_left(self) -> Direction {
+        match self {
+            Direction::North => Direction::West,
+            Direction::East => Direction::North,
+            Direction::South => Direction::East,
+            Direction::West => Direction::South,
+        }
+    }
+
+    pub fn turn_right(self) -> Direction {
+        match self {
+            Direction::North => Direction::East,
+            Direction::East => Direction::South,
+            Direction::South => Direction::West,
+            Direction::West => Direction::North,
+        }
+    }
+
+    pub fn reverse(self) -> Direction {
+        match self {
+            Direction::North => Direction::South,
+            Direction::East => Direction::West,
+            Direction::South => Direction::North,
+            Direction::West => Direction::East,
+        }
+    }
+
+    pub fn is_vertical(self) -> bool {
+        matches!(self, Direction::North | Direction::South)
+    }
+
+    // Direction after bouncing off a '/' or '\' mirror
+    pub fn reflect(self, mirror: char) -> Direction {
+        match (mirror, self.is_vertical()) {
+            ('/', true) | ('\\', false) => self.turn_right(),
+            ('/', false) | ('\\', true) => self.turn_left(),
+            _ => panic!("Unexpected mirror {:?}", mirror),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_turns() {
+        for dir in Direction::ALL {
+            assert_eq!(dir, dir.turn_left().turn_right());
+            assert_eq!(dir.reverse(), dir.turn_right().turn_right());
+            assert_eq!(dir, dir.reflect('/').reflect('/'));
+            assert_eq!(dir, dir.reflect('\\').reflect('\\'));
+        }
+        assert_eq!(Direction::North, Direction::East.reflect('/'));
+        assert_eq!(Direction::South, Direction::East.reflect('\\'));
+        assert_eq!(Direction::West, Direction::South.reflect('/'));
+        assert_eq!(Direction::East, Direction::South.reflect('\\'));
+    }
+
+    #[test]
+    fn test_step_within_bounds() {
+        let bounds = Bounds::new(3, 4);
+        let corner = Point::new(0, 3);
+        assert_eq!(None, corner.step(Direction::North, bounds));
+        assert_eq!(None, corner.step(Direction::East, bounds));
+        assert_eq!(
+            Some(Point::new(1, 3)),
+            corner.step(Direction::South, bounds)
+        );
+        assert_eq!(
+            Some(Point::new(0, 0)),
+            corner.step_by(Direction::West, 3, bounds)
+        );
+        assert_eq!(None, corner.step_by(Direction::South, 3, bounds));
+    }
+}
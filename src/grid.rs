@@ -0,0 +1,306 @@
+use crate::error::ParseError;
+use crate::geometry::{Bounds, Direction, Point};
+use crate::input;
+use std::fmt::{self, Display};
+use std::ops::{Index, IndexMut};
+
+// Rectangular grid of cells addressed by (row, col), stored row by row
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+// Offsets of the 8 neighbors clockwise from north-west
+const OFFSETS8: [(isize, isize); 8] = [
+    (-1, -1),
+    (-1, 0),
+    (-1, 1),
+    (0, 1),
+    (1, 1),
+    (1, 0),
+    (1, -1),
+    (0, -1),
+];
+
+impl<T> Grid<T> {
+    pub fn new(width: usize, height: usize, fill: T) -> Grid<T>
+    where
+        T: Clone,
+    {
+        Grid {
+            width,
+            height,
+            cells: vec![fill; width * height],
+        }
+    }
+
+    // Grid from rows of the same length
+    pub fn from_rows(rows: Vec<Vec<T>>) -> Grid<T> {
+        let height = rows.len();
+        let width = rows.first().map(|r| r.len()).unwrap_or(0);
+        assert!(
+            rows.iter().all(|r| r.len() == width),
+            "Rows of a grid should have the same length"
+        );
+        Grid {
+            width,
+            height,
+            cells: rows.into_iter().flatten().collect(),
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn bounds(&self) -> Bounds {
+        Bounds::new(self.height, self.width)
+    }
+
+    pub fn contains(&self, (row, col): (usize, usize)) -> bool {
+        row < self.height && col < self.width
+    }
+
+    pub fn get(&self, pos: (usize, usize)) -> Option<&T> {
+        self.contains(pos)
+            .then(|| &self.cells[pos.0 * self.width + pos.1])
+    }
+
+    pub fn get_mut(&mut self, pos: (usize, usize)) -> Option<&mut T> {
+        if self.contains(pos) {
+            Some(&mut self.cells[pos.0 * self.width + pos.1])
+        } else {
+            None
+        }
+    }
+
+    pub fn row(&self, row: usize) -> &[T] {
+        &self.cells[row * self.width..(row + 1) * self.width]
+    }
+
+    pub fn row_mut(&mut self, row: usize) -> &mut [T] {
+        &mut self.cells[row * self.width..(row + 1) * self.width]
+    }
+
+    pub fn rows(&self) -> impl DoubleEndedIterator<Item = &[T]> + ExactSizeIterator {
+        // chunks panics on a zero width, so a grid of empty rows gives them one by one
+        (0..self.height).map(|row| self.row(row))
+    }
+
+    pub fn column(&self, col: usize) -> impl DoubleEndedIterator<Item = &T> + ExactSizeIterator {
+        assert!(col < self.width, "Column {} out of the grid", col);
+        (0..self.height).map(move |row| &self.cells[row * self.width + col])
+    }
+
+    pub fn columns(&self) -> impl DoubleEndedIterator<Item = impl DoubleEndedIterator<Item = &T>> {
+        (0..self.width).map(|col| self.column(col))
+    }
+
+    // Every position row by row
+    pub fn positions(&self) -> impl Iterator<Item = (usize, usize)> {
+        let width = self.width;
+        (0..self.height).flat_map(move |row| (0..width).map(move |col| (row, col)))
+    }
+
+    // Every cell with its position row by row
+    pub fn iter(&self) -> impl Iterator<Item = ((usize, usize), &T)> {
+        self.positions().zip(self.cells.iter())
+    }
+
+    pub fn find(&self, mut pred: impl FnMut(&T) -> bool) -> Option<(usize, usize)> {
+        self.iter().find(|(_, c)| pred(c)).map(|(pos, _)| pos)
+    }
+
+    pub fn count(&self, mut pred: impl FnMut(&T) -> bool) -> usize {
+        self.cells.iter().filter(|c| pred(c)).count()
+    }
+
+    // Position moved by the offset, None if it leaves the grid
+    pub fn offset(
+        &self,
+        (row, col): (usize, usize),
+        (d_row, d_col): (isize, isize),
+    ) -> Option<(usize, usize)> {
+        let pos = (
+            row.checked_add_signed(d_row)?,
+            col.checked_add_signed(d_col)?,
+        );
+        self.contains(pos).then_some(pos)
+    }
+
+    // Orthogonal neighbors of the position within the grid
+    pub fn neighbors4(&self, pos: (usize, usize)) -> impl Iterator<Item = (usize, usize)> + '_ {
+        Direction::ALL
+            .iter()
+            .filter_map(move |d| self.offset(pos, d.delta()))
+    }
+
+    // Orthogonal and diagonal neighbors of the position within the grid
+    pub fn neighbors8(&self, pos: (usize, usize)) -> impl Iterator<Item = (usize, usize)> + '_ {
+        OFFSETS8.iter().filter_map(move |&d| self.offset(pos, d))
+    }
+
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> Grid<U> {
+        Grid {
+            width: self.width,
+            height: self.height,
+            cells: self.cells.iter().map(f).collect(),
+        }
+    }
+
+    // Rows become columns
+    pub fn transpose(&self) -> Grid<T>
+    where
+        T: Clone,
+    {
+        Grid {
+            width: self.height,
+            height: self.width,
+            cells: self.columns().flatten().cloned().collect(),
+        }
+    }
+
+    // The first column becomes the first row read backwards
+    pub fn rotate_clockwise(&self) -> Grid<T>
+    where
+        T: Clone,
+    {
+        Grid {
+            width: self.height,
+            height: self.width,
+            cells: self.columns().flat_map(|c| c.rev()).cloned().collect(),
+        }
+    }
+
+    // The last column becomes the first row
+    pub fn rotate_counterclockwise(&self) -> Grid<T>
+    where
+        T: Clone,
+    {
+        Grid {
+            width: self.height,
+            height: self.width,
+            cells: self.columns().rev().flatten().cloned().collect(),
+        }
+    }
+}
+
+impl Grid<char> {
+    // Grid of the lines, every char should come from the `alphabet`
+    // and every line should be as wide as the first one
+    pub fn parse(lines: &[String], alphabet: &str) -> Result<Grid<char>, ParseError> {
+        if let Some(e) = input::grid_problems(lines, alphabet).into_iter().next() {
+            return Err(e);
+        }
+        Ok(Grid::from_rows(
+            lines.iter().map(|line| line.chars().collect()).collect(),
+        ))
+    }
+}
+
+impl<T> Index<(usize, usize)> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, (row, col): (usize, usize)) -> &T {
+        assert!(col < self.width, "Column {} out of the grid", col);
+        &self.cells[row * self.width + col]
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for Grid<T> {
+    fn index_mut(&mut self, (row, col): (usize, usize)) -> &mut T {
+        assert!(col < self.width, "Column {} out of the grid", col);
+        &mut self.cells[row * self.width + col]
+    }
+}
+
+impl<T> Index<Point> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, p: Point) -> &T {
+        &self[(p.row, p.col)]
+    }
+}
+
+impl<T> IndexMut<Point> for Grid<T> {
+    fn index_mut(&mut self, p: Point) -> &mut T {
+        &mut self[(p.row, p.col)]
+    }
+}
+
+// Rows on separate lines, cells without separators
+impl<T: Display> Display for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (idx, row) in self.rows().enumerate() {
+            if idx > 0 {
+                writeln!(f)?;
+            }
+            for cell in row {
+                write!(f, "{}", cell)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grid(s: &str) -> Grid<char> {
+        let lines: Vec<String> = s.lines().map(|l| l.to_string()).collect();
+        Grid::parse(&lines, "abcdef.#").unwrap()
+    }
+
+    #[test]
+    fn test_parse_and_display() {
+        let g = grid("abc\ndef");
+        assert_eq!((3, 2), (g.width(), g.height()));
+        assert_eq!('f', g[(1, 2)]);
+        assert_eq!(None, g.get((2, 0)));
+        assert_eq!("abc\ndef", g.to_string());
+        let lines = vec!["ab".to_string(), "a".to_string()];
+        assert_eq!(2, Grid::parse(&lines, "ab").unwrap_err().line);
+        let lines = vec!["ax".to_string()];
+        assert_eq!(2, Grid::parse(&lines, "ab").unwrap_err().column);
+    }
+
+    #[test]
+    fn test_views() {
+        let g = grid("abc\ndef");
+        assert_eq!(&['d', 'e', 'f'], g.row(1));
+        assert_eq!(vec!['b', 'e'], g.column(1).copied().collect::<Vec<_>>());
+        assert_eq!(Some((1, 1)), g.find(|c| *c == 'e'));
+        assert_eq!(6, g.count(|_| true));
+    }
+
+    #[test]
+    fn test_neighbors() {
+        let g = grid("...\n...\n...");
+        assert_eq!(
+            vec![(0, 1), (1, 0)],
+            g.neighbors4((0, 0)).collect::<Vec<_>>()
+        );
+        assert_eq!(4, g.neighbors4((1, 1)).count());
+        assert_eq!(3, g.neighbors8((2, 2)).count());
+        assert_eq!(8, g.neighbors8((1, 1)).count());
+        assert_eq!(None, g.offset((0, 2), (0, 1)));
+        assert_eq!(Some((2, 1)), g.offset((0, 2), (2, -1)));
+    }
+
+    #[test]
+    fn test_transpose_and_rotate() {
+        let g = grid("abc\ndef");
+        assert_eq!("ad\nbe\ncf", g.transpose().to_string());
+        assert_eq!("da\neb\nfc", g.rotate_clockwise().to_string());
+        assert_eq!("cf\nbe\nad", g.rotate_counterclockwise().to_string());
+        let full_turn = (0..4).fold(g.clone(), |g, _| g.rotate_clockwise());
+        assert_eq!(g, full_turn);
+    }
+}
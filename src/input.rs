@@ -0,0 +1,145 @@
+use crate::error::{ParseError, Result};
+use crate::grid::Grid;
+use std::cell::Cell;
+use std::fs::File;
+use std::io::{self, BufRead};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+// Reader of the input file, "-" stands for stdin
+pub fn open(input_path: &Path) -> Result<Box<dyn BufRead>> {
+    if input_path == Path::new("-") {
+        Ok(Box::new(io::stdin().lock()))
+    } else {
+        let file = File::open(input_path)?;
+        Ok(Box::new(io::BufReader::new(file)))
+    }
+}
+
+thread_local! {
+    // Time spent in `load` on this thread, the benchmark uses it to tell parsing from solving
+    static PARSE_TIME: Cell<Duration> = const { Cell::new(Duration::ZERO) };
+}
+
+// Open the input file and parse it with `parse`, accounting the time spent
+pub fn load<T, F>(input_path: &Path, parse: F) -> Result<T>
+where
+    F: FnOnce(Box<dyn BufRead>) -> Result<T>,
+{
+    let start = Instant::now();
+    let result = parse(open(input_path)?);
+    PARSE_TIME.with(|t| t.set(t.get() + start.elapsed()));
+    result
+}
+
+// Time spent loading inputs on this thread since the previous call
+pub fn take_parse_time() -> Duration {
+    PARSE_TIME.with(|t| t.replace(Duration::ZERO))
+}
+
+// Parse every line of the input with `parse`, attaching line numbers to parse errors
+pub fn parse_lines<T, F>(reader: impl BufRead, mut parse: F) -> Result<Vec<T>>
+where
+    F: FnMut(&str) -> Result<T, ParseError>,
+{
+    let mut result = Vec::new();
+    for (idx, line) in reader.lines().enumerate() {
+        let line = line?;
+        result.push(parse(&line).map_err(|e| e.on_line(idx + 1))?);
+    }
+    Ok(result)
+}
+
+pub fn read_lines(reader: impl BufRead) -> Result<Vec<String>> {
+    parse_lines(reader, |line| Ok(line.to_string()))
+}
+
+// Parse a single row of a char grid, every char should come from the `alphabet`
+pub fn parse_grid_row(line: &str, alphabet: &str) -> Result<Vec<char>, ParseError> {
+    match line.char_indices().find(|(_, c)| !alphabet.contains(*c)) {
+        Some((idx, c)) => Err(ParseError::new(
+            line,
+            &line[idx..idx + c.len_utf8()],
+            format!("one of '{}'", alphabet),
+        )),
+        None => Ok(line.chars().collect()),
+    }
+}
+
+// Parse the input as a rectangular char grid made of the `alphabet` chars
+pub fn parse_grid(reader: impl BufRead, alphabet: &str) -> Result<Grid<char>> {
+    let lines = read_lines(reader)?;
+    Ok(Grid::parse(&lines, alphabet)?)
+}
+
+// Every row of the grid which has chars out of the `alphabet` or another width than the first row.
+// Line numbers are relative to `lines`.
+pub fn grid_problems(lines: &[String], alphabet: &str) -> Vec<ParseError> {
+    let width = lines.first().map(|l| l.chars().count());
+    let mut result = Vec::new();
+    for (idx, line) in lines.iter().enumerate() {
+        let problem = match parse_grid_row(line, alphabet) {
+            Err(e) => Some(e),
+            Ok(row) if Some(row.len()) != width => Some(ParseError::new(
+                line,
+                line,
+                format!("row of width {}", width.unwrap_or(0)),
+            )),
+            Ok(_) => None,
+        };
+        result.extend(problem.map(|e| e.on_line(idx + 1)));
+    }
+    result
+}
+
+// Problems of every line of the input which is parsed line by line with `parse`
+pub fn check_lines<T, F>(input_path: &Path, mut parse: F) -> Result<Vec<ParseError>>
+where
+    F: FnMut(&str) -> Result<T, ParseError>,
+{
+    let lines = load(input_path, read_lines)?;
+    Ok(lines
+        .iter()
+        .enumerate()
+        .filter_map(|(idx, line)| parse(line).err().map(|e| e.on_line(idx + 1)))
+        .collect())
+}
+
+// Problems of the input which is a char grid made of the `alphabet` chars
+pub fn check_grid(input_path: &Path, alphabet: &str) -> Result<Vec<ParseError>> {
+    let lines = load(input_path, read_lines)?;
+    Ok(grid_problems(&lines, alphabet))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Error;
+
+    #[test]
+    fn test_parse_grid() {
+        let grid = parse_grid("#.\n.#".as_bytes(), ".#").unwrap();
+        assert_eq!("#.\n.#", grid.to_string());
+        match parse_grid("#.\n.".as_bytes(), ".#") {
+            Err(Error::Parse(e)) => assert_eq!(2, e.line),
+            r => panic!("Expected parse error, got {:?}", r),
+        }
+    }
+
+    #[test]
+    fn test_grid_problems() {
+        let lines: Vec<String> = ["#.#", "#x", "..", "#.."]
+            .iter()
+            .map(|l| l.to_string())
+            .collect();
+        let problems = grid_problems(&lines, ".#");
+        assert_eq!(
+            vec![(2, 2), (3, 1)],
+            problems
+                .iter()
+                .map(|e| (e.line, e.column))
+                .collect::<Vec<_>>()
+        );
+        assert_eq!("row of width 3", problems[1].expected);
+    }
+}
@@ -0,0 +1,232 @@
+use std::fmt::{self, Display};
+use std::ops::Range;
+
+// Set of numbers stored as half-open ranges.
+// The ranges are kept normalized: sorted, non-empty and neither overlapping
+// nor touching each other, so equal sets have equal ranges.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct IntervalSet {
+    ranges: Vec<Range<u64>>,
+}
+
+impl IntervalSet {
+    pub fn new() -> IntervalSet {
+        IntervalSet { ranges: Vec::new() }
+    }
+
+    // Set of the numbers in any of the ranges, which may overlap or be empty
+    pub fn from_ranges(ranges: impl IntoIterator<Item = Range<u64>>) -> IntervalSet {
+        IntervalSet {
+            ranges: normalize(ranges.into_iter().collect()),
+        }
+    }
+
+    pub fn ranges(&self) -> &[Range<u64>] {
+        &self.ranges
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    // Number of numbers in the set
+    pub fn cardinality(&self) -> u64 {
+        self.ranges.iter().map(|r| r.end - r.start).sum()
+    }
+
+    pub fn min(&self) -> Option<u64> {
+        self.ranges.first().map(|r| r.start)
+    }
+
+    pub fn contains(&self, x: u64) -> bool {
+        // the first range which ends after x is the only one which could hold it
+        let idx = self.ranges.partition_point(|r| r.end <= x);
+        self.ranges.get(idx).is_some_and(|r| r.start <= x)
+    }
+
+    pub fn union(&self, other: &IntervalSet) -> IntervalSet {
+        IntervalSet::from_ranges(self.ranges.iter().chain(&other.ranges).cloned())
+    }
+
+    pub fn intersection(&self, other: &IntervalSet) -> IntervalSet {
+        // both lists are sorted, so they are walked together
+        let mut ranges = Vec::new();
+        let (mut i, mut j) = (0, 0);
+        while i < self.ranges.len() && j < other.ranges.len() {
+            let (a, b) = (&self.ranges[i], &other.ranges[j]);
+            let start = a.start.max(b.start);
+            let end = a.end.min(b.end);
+            if start < end {
+                ranges.push(start..end);
+            }
+            // the range which ends first cannot meet any later range of the other set
+            if a.end < b.end {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+        IntervalSet { ranges }
+    }
+
+    // Numbers of this set which are not in the other one
+    pub fn difference(&self, other: &IntervalSet) -> IntervalSet {
+        let mut ranges = Vec::new();
+        let mut j = 0;
+        for range in &self.ranges {
+            let mut start = range.start;
+            // skip the ranges of the other set which end before this one
+            while j < other.ranges.len() && other.ranges[j].end <= start {
+                j += 1;
+            }
+            let mut k = j;
+            while k < other.ranges.len() && other.ranges[k].start < range.end {
+                let cut = &other.ranges[k];
+                if cut.start > start {
+                    ranges.push(start..cut.start);
+                }
+                start = start.max(cut.end);
+                k += 1;
+            }
+            if start < range.end {
+                ranges.push(start..range.end);
+            }
+        }
+        IntervalSet { ranges }
+    }
+
+    // Numbers below `point` and numbers from `point` on
+    pub fn split_at(&self, point: u64) -> (IntervalSet, IntervalSet) {
+        let below = IntervalSet::from(0..point);
+        (self.intersection(&below), self.difference(&below))
+    }
+
+    // Every number moved by `offset`, which should keep them within u64
+    pub fn shift(&self, offset: i64) -> IntervalSet {
+        let move_by = |x: u64| {
+            x.checked_add_signed(offset)
+                .unwrap_or_else(|| panic!("Shifting {} by {} leaves u64", x, offset))
+        };
+        IntervalSet {
+            ranges: self
+                .ranges
+                .iter()
+                .map(|r| move_by(r.start)..move_by(r.end))
+                .collect(),
+        }
+    }
+}
+
+// Sort the ranges, drop the empty ones and merge the ones which overlap or touch
+fn normalize(mut ranges: Vec<Range<u64>>) -> Vec<Range<u64>> {
+    ranges.retain(|r| !r.is_empty());
+    ranges.sort_by_key(|r| r.start);
+    let mut result: Vec<Range<u64>> = Vec::with_capacity(ranges.len());
+    for range in ranges {
+        match result.last_mut() {
+            Some(last) if range.start <= last.end => last.end = last.end.max(range.end),
+            _ => result.push(range),
+        }
+    }
+    result
+}
+
+impl From<Range<u64>> for IntervalSet {
+    fn from(range: Range<u64>) -> IntervalSet {
+        IntervalSet::from_ranges([range])
+    }
+}
+
+impl FromIterator<Range<u64>> for IntervalSet {
+    fn from_iter<I: IntoIterator<Item = Range<u64>>>(ranges: I) -> IntervalSet {
+        IntervalSet::from_ranges(ranges)
+    }
+}
+
+// "{1..3, 7..9}"
+impl Display for IntervalSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let ranges: Vec<String> = self.ranges.iter().map(|r| format!("{:?}", r)).collect();
+        write!(f, "{{{}}}", ranges.join(", "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+    use std::collections::BTreeSet;
+
+    // Numbers of the sets in the tests stay below this
+    const DOMAIN: u64 = 64;
+
+    // Random set of a few ranges, which may overlap, touch or be empty
+    fn random_set(rng: &mut Rng) -> IntervalSet {
+        (0..rng.below(5))
+            .map(|_| {
+                let start = rng.range(0..DOMAIN);
+                start..rng.range(start..DOMAIN + 1)
+            })
+            .collect()
+    }
+
+    fn naive(set: &IntervalSet) -> BTreeSet<u64> {
+        set.ranges().iter().flat_map(|r| r.clone()).collect()
+    }
+
+    fn is_normalized(set: &IntervalSet) -> bool {
+        set.ranges().iter().all(|r| !r.is_empty())
+            && set.ranges().windows(2).all(|w| w[0].end < w[1].start)
+    }
+
+    #[test]
+    fn test_normalize() {
+        let set = IntervalSet::from_ranges([5..7, 1..3, 3..4, 8..8, 6..10]);
+        assert_eq!(&[1..4, 5..10], set.ranges());
+        assert_eq!(8, set.cardinality());
+        assert_eq!("{1..4, 5..10}", set.to_string());
+    }
+
+    #[test]
+    fn test_agrees_with_naive_set() {
+        let mut rng = Rng::new(2023);
+        for _ in 0..500 {
+            let (a, b) = (random_set(&mut rng), random_set(&mut rng));
+            let (naive_a, naive_b) = (naive(&a), naive(&b));
+            let results: [(IntervalSet, BTreeSet<u64>); 3] = [
+                (a.union(&b), naive_a.union(&naive_b).copied().collect()),
+                (
+                    a.intersection(&b),
+                    naive_a.intersection(&naive_b).copied().collect(),
+                ),
+                (
+                    a.difference(&b),
+                    naive_a.difference(&naive_b).copied().collect(),
+                ),
+            ];
+            for (set, expected) in results {
+                assert!(is_normalized(&set), "{} is not normalized", set);
+                assert_eq!(expected, naive(&set), "{} and {}", a, b);
+                assert_eq!(expected.len() as u64, set.cardinality());
+            }
+            let point = rng.range(0..DOMAIN + 1);
+            let (below, above) = a.split_at(point);
+            assert_eq!(
+                naive_a.range(..point).copied().collect::<BTreeSet<_>>(),
+                naive(&below)
+            );
+            assert_eq!(
+                naive_a.range(point..).copied().collect::<BTreeSet<_>>(),
+                naive(&above)
+            );
+            let shifted = a.shift(100).shift(-30);
+            assert_eq!(
+                naive_a.iter().map(|x| x + 70).collect::<BTreeSet<_>>(),
+                naive(&shifted)
+            );
+            let x = rng.range(0..DOMAIN);
+            assert_eq!(naive_a.contains(&x), a.contains(x));
+            assert_eq!(naive_a.first().copied(), a.min());
+        }
+    }
+}
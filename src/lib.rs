@@ -0,0 +1,132 @@
+pub mod answer;
+pub mod bench;
+pub mod cycle;
+pub mod day1;
+pub mod day10;
+pub mod day11;
+pub mod day12;
+pub mod day13;
+pub mod day14;
+pub mod day15;
+pub mod day16;
+pub mod day18;
+pub mod day19;
+pub mod day2;
+pub mod day3;
+pub mod day4;
+pub mod day5;
+pub mod day6;
+pub mod day7;
+pub mod day8;
+pub mod day9;
+pub mod diff;
+pub mod error;
+pub mod geometry;
+pub mod grid;
+pub mod input;
+pub mod interval;
+pub mod json;
+pub mod log;
+pub mod parse;
+pub mod progress;
+pub mod rng;
+pub mod runner;
+pub mod scaffold;
+pub mod verify;
+
+// compiled only to check that new days start from a valid module,
+// it is not registered so nothing calls it
+#[cfg(test)]
+#[allow(dead_code)]
+mod template;
+
+macro_rules! day {
+    ($day:ident, $title:literal, $(($fn:ident, $kind:ident, $desc:literal)),*) => {
+        (
+            stringify!($day),
+            $crate::runner::Day {
+                title: $title,
+                check: $day::check,
+                generate: $day::generate,
+                parts: vec![$($crate::runner::Part {
+                    run: $day::$fn,
+                    kind: $crate::runner::PartKind::$kind,
+                    description: $desc,
+                }),*],
+            },
+        )
+    };
+}
+
+macro_rules! gen_days {
+    ($($day:tt),*) => {
+        {
+            let map: $crate::runner::Days = std::collections::HashMap::from([
+                $(day!$day),*
+            ]);
+            map
+        }
+    };
+}
+
+// Registry of all solved days: day name -> its title and parts in order
+pub fn days() -> runner::Days {
+    gen_days! {
+        (day1, "Trebuchet?!",
+            (part1, Official, "Sum of calibration values made of the first and last digits"),
+            (part2, Official, "Sum of calibration values with spelled out digits")),
+        (day2, "Cube Conundrum",
+            (part1, Official, "Sum of ids of games possible with 12 red, 13 green and 14 blue cubes"),
+            (part2, Official, "Sum of powers of the minimal cube sets")),
+        (day3, "Gear Ratios",
+            (part1, Official, "Sum of numbers adjacent to a symbol"),
+            (part2, Official, "Sum of gear ratios")),
+        (day4, "Scratchcards",
+            (part1, Official, "Total points of the scratchcards"),
+            (part2, Official, "Total number of scratchcards with the won copies")),
+        (day5, "If You Give A Seed A Fertilizer",
+            (part1, Official, "Lowest location of the listed seeds"),
+            (part2, Official, "Lowest location of the seed ranges, seed by seed"),
+            (part3, Alternative, "Part 2 with seed ranges checked in parallel"),
+            (part4, Alternative, "Part 2 by mapping whole ranges through the rules")),
+        (day6, "Wait For It",
+            (part1, Official, "Product of the numbers of ways to win each race"),
+            (part2, Official, "Number of ways to win the single race with kerned numbers")),
+        (day7, "Camel Cards",
+            (part1, Official, "Total winnings of the ranked hands"),
+            (part2, Official, "Total winnings with J as jokers")),
+        (day8, "Haunted Wasteland",
+            (part1, Official, "Steps from AAA to ZZZ"),
+            (part2, Official, "Steps until all ghosts stand on Z nodes, as LCM of their cycles"),
+            (part3, Experimental, "Z nodes hit by each ghost until its walk repeats")),
+        (day9, "Mirage Maintenance",
+            (part1, Official, "Sum of extrapolated next values"),
+            (part2, Official, "Sum of extrapolated previous values")),
+        (day10, "Pipe Maze",
+            (part1, Official, "Steps to the farthest point of the loop"),
+            (part2, Official, "Number of tiles enclosed by the loop")),
+        (day11, "Cosmic Expansion",
+            (part1, Official, "Sum of distances between galaxies with doubled empty space"),
+            (part2, Official, "Sum of distances with empty space expanded a million times")),
+        (day12, "Hot Springs",
+            (part1, Experimental, "Unfinished: counts one arrangement for each of the first two records"),
+            (part2, Official, "Sum of counts of arrangements of unfolded records")),
+        (day13, "Point of Incidence",
+            (part1, Official, "Summary of mirror lines of the patterns"),
+            (part2, Official, "Summary of mirror lines with one smudge fixed")),
+        (day14, "Parabolic Reflector Dish",
+            (part1, Official, "Load on the north beams after tilting north"),
+            (part2, Official, "Load on the north beams after a billion spin cycles")),
+        (day15, "Lens Library",
+            (part1, Official, "Sum of hashes of the initialization steps"),
+            (part2, Official, "Focusing power of the lens configuration")),
+        (day16, "The Floor Will Be Lava",
+            (part1, Official, "Tiles energized by the beam entering at the top left"),
+            (part2, Official, "Most tiles energized by a beam entering from any edge")),
+        (day18, "Lavaduct Lagoon",
+            (part1, Official, "Cubic meters of lava the lagoon holds")),
+        (day19, "Aplenty",
+            (part1, Official, "Sum of ratings of the accepted parts"),
+            (part2, Official, "Number of rating combinations which get accepted"))
+    }
+}
@@ -0,0 +1,78 @@
+use std::sync::atomic::{AtomicU8, Ordering};
+
+// Verbosity of the diagnostic output on stderr, from the quietest to the noisiest.
+// Answers and tables go to stdout and are never affected by it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    Error,
+    Info,
+    Debug,
+    Trace,
+}
+
+const LEVELS: [Level; 4] = [Level::Error, Level::Info, Level::Debug, Level::Trace];
+
+static MAX_LEVEL: AtomicU8 = AtomicU8::new(Level::Info as u8);
+
+pub fn set_level(level: Level) {
+    MAX_LEVEL.store(level as u8, Ordering::Relaxed);
+}
+
+pub fn enabled(level: Level) -> bool {
+    level as u8 <= MAX_LEVEL.load(Ordering::Relaxed)
+}
+
+// Level for the given number of -v and -q flags: each -v adds a level above info,
+// any -q leaves only errors
+pub fn level_from_flags(verbose: usize, quiet: bool) -> Level {
+    if quiet {
+        Level::Error
+    } else {
+        LEVELS[(Level::Info as usize + verbose).min(LEVELS.len() - 1)]
+    }
+}
+
+// The arguments are not evaluated when the level is disabled,
+// so expensive debug dumps cost nothing on normal runs
+#[macro_export]
+macro_rules! log {
+    ($level:expr, $($arg:tt)+) => {
+        if $crate::log::enabled($level) {
+            eprintln!($($arg)+);
+        }
+    };
+}
+
+#[macro_export]
+macro_rules! error {
+    ($($arg:tt)+) => { $crate::log!($crate::log::Level::Error, $($arg)+) };
+}
+
+#[macro_export]
+macro_rules! info {
+    ($($arg:tt)+) => { $crate::log!($crate::log::Level::Info, $($arg)+) };
+}
+
+#[macro_export]
+macro_rules! debug {
+    ($($arg:tt)+) => { $crate::log!($crate::log::Level::Debug, $($arg)+) };
+}
+
+#[macro_export]
+macro_rules! trace {
+    ($($arg:tt)+) => { $crate::log!($crate::log::Level::Trace, $($arg)+) };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_level_from_flags() {
+        assert_eq!(Level::Info, level_from_flags(0, false));
+        assert_eq!(Level::Debug, level_from_flags(1, false));
+        assert_eq!(Level::Trace, level_from_flags(2, false));
+        assert_eq!(Level::Trace, level_from_flags(5, false));
+        assert_eq!(Level::Error, level_from_flags(1, true));
+    }
+}
@@ -1,89 +1,280 @@
 use std::env;
-use std::path::Path;
+use std::panic;
+use std::path::{Path, PathBuf};
 use std::process::exit;
+use std::time::{SystemTime, UNIX_EPOCH};
 
-mod day1;
-mod day10;
-mod day11;
-mod day12;
-mod day13;
-mod day14;
-mod day15;
-mod day16;
-mod day18;
-mod day19;
-mod day2;
-mod day3;
-mod day4;
-mod day5;
-mod day6;
-mod day7;
-mod day8;
-mod day9;
-
-#[macro_export]
-macro_rules! day {
-    ($day:ident, $($fn:ident),*) => {
-        (stringify!($day), vec![$($day::$fn),*])
-    };
-}
-
-#[macro_export]
-macro_rules! gen_days {
-    ($($day:tt),*) => {
-        {
-            let map: std::collections::HashMap<&str, Vec<fn(&Path) -> ()>> = std::collections::HashMap::from([
-                $(day!$day),*
-            ]);
-            map
-        }
-    };
-}
+use rust_aoc_2023::rng::Rng;
+use rust_aoc_2023::{bench, days, diff, error, info, log, progress, runner, scaffold, verify};
 
 fn main() {
-    let days = gen_days!(
-        (day1, part1, part2),
-        (day2, part1, part2),
-        (day3, part1, part2),
-        (day4, part1, part2),
-        (day5, part1, part2, part3, part4),
-        (day6, part1, part2),
-        (day7, part1, part2),
-        (day8, part1, part2, part3),
-        (day9, part1, part2),
-        (day10, part1, part2),
-        (day11, part1, part2),
-        (day12, part1, part2),
-        (day13, part1, part2),
-        (day14, part1, part2),
-        (day15, part1, part2),
-        (day16, part1, part2),
-        (day18, part1, part2),
-        (day19, part1, part2)
-    );
+    let days = days();
 
     // Program arguments:
-    //  rust-aoc <day> <part> <input-file>
-    let args: Vec<String> = env::args().collect();
-    if args.len() != 4 {
-        println!("Usage: <runner> <part> <path-to-input>");
+    //  rust-aoc [-v|-vv|-q] <day> <part> [input-file] [--example N] [--inputs DIR] [--format text|json] [--timeout T]
+    //  rust-aoc [-v|-vv|-q] bench <day> <part> [input-file] [--iterations N] [--example N] [--inputs DIR]
+    //  rust-aoc [-v|-vv|-q] check <day> [input-file] [--example N] [--inputs DIR]
+    //  rust-aoc [-v|-vv|-q] generate <day> [--size N] [--seed S]
+    //  rust-aoc [-v|-vv|-q] diff <day> <part> <part> [input-file] [--example N] [--count N] [--size N] [--seed S] [--timeout T]
+    //  rust-aoc [-v|-vv|-q] all [inputs-dir] [--format text|json] [--threads N] [--timeout T]
+    //  rust-aoc [-v|-vv|-q] verify [answers-file] [inputs-dir]
+    //  rust-aoc list
+    //  rust-aoc new <day>
+    // <input-file> may be "-" to read the input from stdin. Without it the input is
+    // <inputs-dir>/<day>.txt, or the example <day><N>.test with --example N.
+    // check only parses the input and reports every problem found in it, the solver is not run.
+    // generate prints a random valid input; the seed is logged, so the same input can be generated again.
+    // diff compares two parts on the input, or on --count generated inputs of growing size,
+    // and prints the smallest input on which they disagree.
+    // --timeout 30s stops waiting for a part after the given time, in seconds by default.
+    // --threads N sets the size of the rayon pool shared by `all` and the parallel solutions.
+    // Long computations show their progress on stderr when it is a terminal.
+    // Verbosity flags may appear anywhere: -v shows debug output, -vv traces, -q leaves only errors
+    let (flags, mut args): (Vec<String>, Vec<String>) =
+        env::args().partition(|a| is_verbosity_flag(a));
+    let verbose = flags.iter().map(|f| f.matches('v').count()).sum();
+    let quiet = flags.iter().any(|f| f == "-q");
+    log::set_level(log::level_from_flags(verbose, quiet));
+    progress::enable_on_terminal(quiet);
+    if let Some(threads) = take_number_option(&mut args, "--threads") {
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build_global()
+            .expect("The global thread pool is configured only once");
+    }
+    let timeout = take_option(&mut args, "--timeout").map(|t| {
+        runner::parse_duration(&t).unwrap_or_else(|| {
+            println!(
+                "Could not parse timeout '{}', it should look like 30, 1.5s or 500ms",
+                t
+            );
+            exit(1);
+        })
+    });
+    let json = match take_option(&mut args, "--format").as_deref() {
+        None | Some("text") => false,
+        Some("json") => true,
+        Some(other) => {
+            println!("Unknown format '{}', it should be text or json", other);
+            exit(1);
+        }
+    };
+    if args.len() >= 2 && args[1] == "all" && args.len() <= 3 {
+        let inputs_dir = Path::new(args.get(2).map(|s| s.as_str()).unwrap_or("inputs"));
+        let runs = runner::run_all(&days, inputs_dir, timeout);
+        progress::stop();
+        if json {
+            runner::print_json(&runs);
+        } else {
+            runner::print_table(&runs);
+        }
+        return;
+    }
+    if args.len() == 3 && args[1] == "new" {
+        let Some(day) = scaffold::parse_day(&args[2]) else {
+            println!(
+                "Could not parse day '{}': it should be a number from 1 to 25",
+                args[2]
+            );
+            exit(1);
+        };
+        match scaffold::new_day(Path::new("."), &day) {
+            Ok(created) => {
+                for path in created {
+                    println!("Created {}", path.display());
+                }
+                println!("Registered {} in src/lib.rs", day);
+            }
+            Err(e) => {
+                println!("Could not create {}: {}", day, e);
+                exit(1);
+            }
+        }
+        return;
+    }
+    if args.len() >= 3 && args[1] == "generate" {
+        let size = take_number_option(&mut args, "--size").unwrap_or(10);
+        let seed = take_seed(&mut args);
+        let Some(day) = days.get(args[2].as_str()).filter(|_| args.len() == 3) else {
+            println!("Usage: <runner> generate <day> [--size N] [--seed S]");
+            exit(1);
+        };
+        info!("{} input of size {} from seed {}", &args[2], size, seed);
+        for line in (day.generate)(&mut Rng::new(seed), size) {
+            println!("{}", line);
+        }
+        return;
+    }
+    if args.len() == 2 && args[1] == "list" {
+        runner::print_list(&days);
+        return;
+    }
+    if args.len() >= 2 && args[1] == "verify" && args.len() <= 4 {
+        let answers_path = Path::new(args.get(2).map(|s| s.as_str()).unwrap_or("answers.txt"));
+        let inputs_dir = Path::new(args.get(3).map(|s| s.as_str()).unwrap_or("inputs"));
+        let mut expectations = verify::load_expectations(answers_path).unwrap_or_else(|e| {
+            println!(
+                "Could not load answers from {}: {}",
+                answers_path.display(),
+                e
+            );
+            exit(1);
+        });
+        // the examples are checked first, they are the quickest
+        let examples = verify::example_expectations(&days, Path::new(".")).unwrap_or_else(|e| {
+            println!("Could not load answers of the examples: {}", e);
+            exit(1);
+        });
+        expectations.splice(0..0, examples);
+        if !verify::verify(&days, &expectations, inputs_dir) {
+            exit(1);
+        }
+        return;
+    }
+    // bench takes the same arguments as a single run, check takes them without the part
+    let bench = args.len() >= 2 && args[1] == "bench";
+    let check = args.len() >= 2 && args[1] == "check";
+    if bench || check {
+        args.remove(1);
+    }
+    let iterations = take_number_option(&mut args, "--iterations").unwrap_or(10);
+    let example = take_number_option(&mut args, "--example");
+    let inputs_dir = take_option(&mut args, "--inputs").unwrap_or_else(|| "inputs".to_string());
+    if args.len() >= 2 && args[1] == "diff" {
+        let count = take_number_option(&mut args, "--count").unwrap_or(100);
+        let max_size = take_number_option(&mut args, "--size").unwrap_or(20);
+        let seed = take_seed(&mut args);
+        let day_entry = args.get(2).and_then(|d| days.get_key_value(d.as_str()));
+        let part = |idx: usize| args.get(idx).and_then(|p| runner::parse_part_number(p));
+        let (Some((&day, entry)), Some(part_a), Some(part_b)) = (day_entry, part(3), part(4))
+        else {
+            println!("Usage: <runner> diff <day> <part> <part> [path-to-input] [--example N] [--count N] [--size N] [--seed S]");
+            exit(1);
+        };
+        if args.len() > 6 || part_a.max(part_b) > entry.parts.len() {
+            println!("Unknown part, {} has only {} parts", day, entry.parts.len());
+            exit(1);
+        }
+        let comparison = diff::Comparison {
+            day,
+            entry,
+            parts: (part_a, part_b),
+            timeout,
+        };
+        let path = match (args.get(5), example) {
+            (Some(path), _) => Some(PathBuf::from(path)),
+            (None, Some(n)) => Some(runner::example_path(Path::new("."), day, n)),
+            (None, None) => None,
+        };
+        // disagreements are reported with the outcomes, panics included
+        let default_hook = panic::take_hook();
+        panic::set_hook(Box::new(|_| {}));
+        let result = match &path {
+            Some(path) => comparison.compare_file(path),
+            None => comparison.search(seed, count, max_size),
+        };
+        panic::set_hook(default_hook);
+        match result {
+            Ok(None) => match &path {
+                Some(path) => println!(
+                    "{} part{} and part{} agree on {}",
+                    day,
+                    part_a,
+                    part_b,
+                    path.display()
+                ),
+                None => println!(
+                    "{} part{} and part{} agree on {} inputs of size up to {} generated from seed {}",
+                    day, part_a, part_b, count, max_size, seed
+                ),
+            },
+            Ok(Some(disagreement)) => {
+                println!("{}", comparison.report(&disagreement));
+                exit(1);
+            }
+            Err(e) => {
+                error!("{}", e);
+                exit(1);
+            }
+        }
+        return;
+    }
+    let path_idx = if check { 2 } else { 3 };
+    if args.len() != path_idx && args.len() != path_idx + 1 {
+        println!(
+            "Usage: <runner> [-v|-vv|-q] <day> <part> [path-to-input] [--example N] [--inputs DIR] [--format text|json] [--timeout T]"
+        );
+        println!("       <runner> [-v|-vv|-q] bench <day> <part> [path-to-input] [--iterations N] [--example N] [--inputs DIR]");
+        println!(
+            "       <runner> [-v|-vv|-q] check <day> [path-to-input] [--example N] [--inputs DIR]"
+        );
+        println!("       <runner> [-v|-vv|-q] diff <day> <part> <part> [path-to-input] [--example N] [--count N] [--size N] [--seed S]");
+        println!("       <runner> [-v|-vv|-q] generate <day> [--size N] [--seed S]");
+        println!("       <runner> [-v|-vv|-q] all [inputs-dir] [--format text|json] [--threads N] [--timeout T]");
+        println!("       <runner> [-v|-vv|-q] verify [answers-file] [inputs-dir]");
+        println!("       <runner> list");
+        println!("       <runner> new <day>");
         println!("Example: rust-aoc day1 part1 test.txt");
+        println!("Example: rust-aoc day5 part2 --example 1");
+        println!("Example: cat test.txt | rust-aoc day1 part1 -");
+        println!("Example: rust-aoc bench day5 part3 --iterations 20");
+        println!("Example: rust-aoc check day19 day191.test");
+        println!("Example: rust-aoc generate day5 --size 100 --seed 42 | rust-aoc day5 part3 -");
+        println!("Example: rust-aoc diff day5 part2 part3 --count 50 --size 10");
+        println!("Example: rust-aoc all inputs");
+        println!("Example: rust-aoc all inputs --format json");
+        println!("Example: rust-aoc all inputs --threads 4 --timeout 30s");
+        println!("Example: rust-aoc verify answers.txt inputs");
         exit(1);
     }
 
-    let day_parts = days
-        .get(&args[1].as_str())
+    let (&day, day_parts) = days
+        .get_key_value(args[1].as_str())
         .unwrap_or_else(|| panic!("I don't know the day {}", &args[1]));
-    let part: usize = if args[2].starts_with("part") {
-        &args[2][4..args[2].len()]
-    } else {
-        args[2].as_str()
-    }
-    .parse::<usize>()
-    .map(|p| p - 1)
-    .unwrap_or_else(|_| panic!("Could not parse part: it should be a number"));
-    let path = Path::new(&args[3]);
+    let path = match (args.get(path_idx), example) {
+        (Some(path), _) => PathBuf::from(path),
+        (None, Some(n)) => runner::example_path(Path::new("."), &args[1], n),
+        (None, None) => runner::input_path(Path::new(&inputs_dir), &args[1]),
+    };
+    if args.len() == path_idx && !path.is_file() {
+        println!("No input file {}", path.display());
+        let dir = path.parent().unwrap_or(Path::new("."));
+        let candidates = runner::candidate_inputs(dir, &args[1]);
+        if candidates.is_empty() {
+            println!("There are no inputs of {} in {}", &args[1], dir.display());
+        } else {
+            println!("Candidates:");
+            for candidate in candidates {
+                println!("  {}", candidate.display());
+            }
+        }
+        exit(1);
+    }
+    let path = path.as_path();
+    if check {
+        match (day_parts.check)(path) {
+            Ok(problems) if problems.is_empty() => {
+                println!("{}: no problems found", path.display())
+            }
+            Ok(problems) => {
+                for problem in &problems {
+                    error!("{}\n", problem.clone().in_file(path));
+                }
+                println!("{}: {} problem(s) found", path.display(), problems.len());
+                exit(1);
+            }
+            Err(e) => {
+                error!("{}", e.in_file(path));
+                exit(1);
+            }
+        }
+        return;
+    }
+    let part: usize = runner::parse_part_number(&args[2])
+        .map(|p| p - 1)
+        .unwrap_or_else(|| panic!("Could not parse part: it should be a number"));
 
+    let day_parts = &day_parts.parts;
     if part >= day_parts.len() {
         println!(
             "Unknown part: {}, that day has only {} parts",
@@ -92,6 +283,88 @@ fn main() {
         );
         exit(1);
     }
-    let part_fn = day_parts[part];
-    part_fn(path);
+    let part_fn = day_parts[part].run;
+    if bench {
+        match bench::bench(part_fn, path, iterations) {
+            Ok(timings) => {
+                println!(
+                    "{} part{} on {}, {} iterations",
+                    &args[1],
+                    part + 1,
+                    path.display(),
+                    iterations
+                );
+                bench::print_report(&timings);
+            }
+            Err(e) => {
+                error!("{}", e.in_file(path));
+                exit(1);
+            }
+        }
+        return;
+    }
+    if json || timeout.is_some() {
+        let run = runner::run_part_with_timeout(day, part + 1, part_fn, path, timeout);
+        progress::stop();
+        if json {
+            println!("{}", run.to_json());
+        } else if let Some(answer) = &run.answer {
+            println!("Result: {}", answer);
+        } else if let Some(failure) = run.failure() {
+            error!("{}", failure);
+        } else {
+            error!("{} part{}: {}", day, part + 1, run.status);
+        }
+        if run.status != runner::Status::Ok {
+            exit(1);
+        }
+        return;
+    }
+    match progress::with_label(format!("{} part{}", day, part + 1), || part_fn(path)) {
+        Ok(answer) => println!("Result: {}", answer),
+        Err(e) => {
+            error!("{}", e.in_file(path));
+            exit(1);
+        }
+    }
+}
+
+fn is_verbosity_flag(arg: &str) -> bool {
+    arg == "-q" || (arg.len() > 1 && arg.starts_with('-') && arg[1..].chars().all(|c| c == 'v'))
+}
+
+// Remove `--name value` from the arguments and return the value
+fn take_option(args: &mut Vec<String>, name: &str) -> Option<String> {
+    let idx = args.iter().position(|a| a == name)?;
+    if idx + 1 >= args.len() {
+        println!("Option {} needs a value", name);
+        exit(1);
+    }
+    let value = args.remove(idx + 1);
+    args.remove(idx);
+    Some(value)
+}
+
+// Seed of the generated inputs, from the clock when it is not given
+fn take_seed(args: &mut Vec<String>) -> u64 {
+    match take_option(args, "--seed") {
+        Some(s) => s.parse().unwrap_or_else(|_| {
+            println!("Option --seed needs a number, got '{}'", s);
+            exit(1);
+        }),
+        None => SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_nanos() as u64)
+            .unwrap_or_default(),
+    }
+}
+
+fn take_number_option(args: &mut Vec<String>, name: &str) -> Option<usize> {
+    take_option(args, name).map(|n| match n.parse::<usize>() {
+        Ok(n) if n > 0 => n,
+        _ => {
+            println!("Option {} needs a positive number, got '{}'", name, n);
+            exit(1);
+        }
+    })
 }
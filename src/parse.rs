@@ -0,0 +1,483 @@
+use crate::error::ParseError;
+use std::str::FromStr;
+
+// Parser combinators for the line based puzzle inputs.
+// A parser takes a cursor into a line and returns the value with the cursor after it.
+// Tokens (literals, numbers and words) skip the whitespace in front of them,
+// so the spacing of the input does not matter. Errors point at the token where
+// the line stops following the grammar.
+
+#[derive(Debug, Clone, Copy)]
+pub struct Cursor<'a> {
+    // the whole line, errors are positioned in it
+    pub line: &'a str,
+    pub rest: &'a str,
+}
+
+pub type PResult<'a, T> = Result<(T, Cursor<'a>), Failure>;
+
+// Why a parser failed. On a `Mismatch` the input does not start like the value,
+// so a combinator may take something else there, like the end of a list.
+// An `Invalid` value has started but is wrong, like a number too big for its type.
+// Parsers made of several parts fail as `Invalid` after their first part.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Failure {
+    Mismatch(ParseError),
+    Invalid(ParseError),
+}
+
+impl From<ParseError> for Failure {
+    fn from(e: ParseError) -> Failure {
+        Failure::Mismatch(e)
+    }
+}
+
+impl Failure {
+    // The value has started, so whatever failed after that makes it invalid
+    fn commit(self) -> Failure {
+        Failure::Invalid(self.into())
+    }
+}
+
+impl From<Failure> for ParseError {
+    fn from(failure: Failure) -> ParseError {
+        match failure {
+            Failure::Mismatch(e) | Failure::Invalid(e) => e,
+        }
+    }
+}
+
+pub trait Parser<'a, T>: Fn(Cursor<'a>) -> PResult<'a, T> {}
+
+impl<'a, T, F: Fn(Cursor<'a>) -> PResult<'a, T>> Parser<'a, T> for F {}
+
+impl<'a> Cursor<'a> {
+    pub fn new(line: &'a str) -> Cursor<'a> {
+        Cursor { line, rest: line }
+    }
+
+    fn advance(self, len: usize) -> Cursor<'a> {
+        Cursor {
+            rest: &self.rest[len..],
+            ..self
+        }
+    }
+
+    fn skip_spaces(self) -> Cursor<'a> {
+        let len = self.rest.len() - self.rest.trim_start().len();
+        self.advance(len)
+    }
+
+    // Offset of the cursor in the line
+    fn offset(&self) -> usize {
+        self.line.len() - self.rest.len()
+    }
+
+    // Error about the next token: a run of letters and digits or a single other char
+    pub fn error(&self, expected: impl Into<String>) -> ParseError {
+        let rest = self.skip_spaces().rest;
+        let len = match rest.chars().next() {
+            None => 0,
+            Some(c) if c.is_alphanumeric() => rest
+                .find(|c: char| !c.is_alphanumeric())
+                .unwrap_or(rest.len()),
+            Some(c) => c.len_utf8(),
+        };
+        ParseError::new(self.line, &rest[..len], expected)
+    }
+}
+
+// Parse the whole line with the parser, only whitespace may follow
+pub fn parse_line<'a, T>(line: &'a str, parser: impl Parser<'a, T>) -> Result<T, ParseError> {
+    let (value, rest) = parser(Cursor::new(line))?;
+    end(rest)?;
+    Ok(value)
+}
+
+pub fn end(c: Cursor<'_>) -> PResult<'_, ()> {
+    let c = c.skip_spaces();
+    if c.rest.is_empty() {
+        Ok(((), c))
+    } else {
+        Err(c.error("end of line").into())
+    }
+}
+
+pub fn literal<'a>(s: &'static str) -> impl Parser<'a, ()> {
+    move |c: Cursor<'a>| {
+        let c = c.skip_spaces();
+        if c.rest.starts_with(s) {
+            Ok(((), c.advance(s.len())))
+        } else {
+            Err(c.error(format!("'{}'", s)).into())
+        }
+    }
+}
+
+// One of the words, as it is written in the input
+pub fn keyword<'a>(words: &'static [&'static str]) -> impl Parser<'a, &'static str> {
+    move |c: Cursor<'a>| {
+        let (found, rest) = word(c)?;
+        match words.iter().find(|w| **w == found) {
+            Some(w) => Ok((*w, rest)),
+            None => Err(c.error(one_of(words)).into()),
+        }
+    }
+}
+
+// "'a', 'b' or 'c'"
+fn one_of(words: &[&str]) -> String {
+    let quoted: Vec<String> = words.iter().map(|w| format!("'{}'", w)).collect();
+    match quoted.split_last() {
+        Some((last, [])) => last.clone(),
+        Some((last, rest)) => format!("{} or {}", rest.join(", "), last),
+        None => "nothing".to_string(),
+    }
+}
+
+// Run of letters and digits
+pub fn word(c: Cursor<'_>) -> PResult<'_, &str> {
+    let c = c.skip_spaces();
+    let len = c
+        .rest
+        .find(|ch: char| !ch.is_alphanumeric())
+        .unwrap_or(c.rest.len());
+    if len == 0 {
+        return Err(c.error("word").into());
+    }
+    Ok((&c.rest[..len], c.advance(len)))
+}
+
+// Run of decimal digits, as it is written
+pub fn digits(c: Cursor<'_>) -> PResult<'_, &str> {
+    let c = c.skip_spaces();
+    let len = c
+        .rest
+        .find(|ch: char| !ch.is_ascii_digit())
+        .unwrap_or(c.rest.len());
+    if len == 0 {
+        return Err(c.error("number").into());
+    }
+    Ok((&c.rest[..len], c.advance(len)))
+}
+
+pub fn uint<T: FromStr>(c: Cursor<'_>) -> PResult<'_, T> {
+    let (s, rest) = digits(c)?;
+    let value = s
+        .parse()
+        .map_err(|_| Failure::Invalid(ParseError::new(c.line, s, "number")))?;
+    Ok((value, rest))
+}
+
+// Number with an optional sign right in front of it
+pub fn int<T: FromStr>(c: Cursor<'_>) -> PResult<'_, T> {
+    let c = c.skip_spaces();
+    let sign_len = usize::from(c.rest.starts_with(['-', '+']));
+    let (s, rest) = digits(c.advance(sign_len)).map_err(|_| c.error("number"))?;
+    if rest.offset() - c.offset() != sign_len + s.len() {
+        // there are spaces between the sign and the digits
+        return Err(c.error("number").into());
+    }
+    let text = &c.rest[..sign_len + s.len()];
+    let value = text
+        .parse()
+        .map_err(|_| Failure::Invalid(ParseError::new(c.line, text, "number")))?;
+    Ok((value, rest))
+}
+
+pub fn map<'a, A, B>(parser: impl Parser<'a, A>, f: impl Fn(A) -> B) -> impl Parser<'a, B> {
+    move |c: Cursor<'a>| parser(c).map(|(value, rest)| (f(value), rest))
+}
+
+pub fn pair<'a, A, B>(
+    first: impl Parser<'a, A>,
+    second: impl Parser<'a, B>,
+) -> impl Parser<'a, (A, B)> {
+    move |c: Cursor<'a>| {
+        let (a, c) = first(c)?;
+        let (b, c) = second(c).map_err(Failure::commit)?;
+        Ok(((a, b), c))
+    }
+}
+
+// The value of `parser` after `prefix`
+pub fn preceded<'a, A, B>(
+    prefix: impl Parser<'a, A>,
+    parser: impl Parser<'a, B>,
+) -> impl Parser<'a, B> {
+    move |c: Cursor<'a>| {
+        let (_, c) = prefix(c)?;
+        parser(c).map_err(Failure::commit)
+    }
+}
+
+// The value of `parser` followed by `suffix`
+pub fn terminated<'a, A, B>(
+    parser: impl Parser<'a, A>,
+    suffix: impl Parser<'a, B>,
+) -> impl Parser<'a, A> {
+    move |c: Cursor<'a>| {
+        let (value, c) = parser(c)?;
+        let (_, c) = suffix(c).map_err(Failure::commit)?;
+        Ok((value, c))
+    }
+}
+
+// Zero or more values, separated by whitespace.
+// The list ends before the first token which does not start a value,
+// a value which has started but is invalid is an error.
+pub fn list<'a, T>(parser: impl Parser<'a, T>) -> impl Parser<'a, Vec<T>> {
+    move |mut c: Cursor<'a>| {
+        let mut result = Vec::new();
+        loop {
+            match parser(c) {
+                Ok((value, rest)) => {
+                    result.push(value);
+                    c = rest;
+                }
+                Err(Failure::Mismatch(_)) => return Ok((result, c)),
+                Err(failure) => return Err(failure),
+            }
+        }
+    }
+}
+
+// One or more values separated by `separator`
+pub fn separated<'a, T, S>(
+    parser: impl Parser<'a, T>,
+    separator: impl Parser<'a, S>,
+) -> impl Parser<'a, Vec<T>> {
+    move |c: Cursor<'a>| {
+        let (value, mut c) = parser(c)?;
+        let mut result = vec![value];
+        while let Ok((_, rest)) = separator(c) {
+            let (value, rest) = parser(rest).map_err(Failure::commit)?;
+            result.push(value);
+            c = rest;
+        }
+        Ok((result, c))
+    }
+}
+
+// Lines of the input between blank lines
+#[derive(Debug, Clone, Copy)]
+pub struct Section<'a> {
+    // 1-based number of the first line in the input
+    pub first_line: usize,
+    pub lines: &'a [String],
+}
+
+// Sections of the lines separated by blank lines, runs of blank lines make no empty sections
+pub fn sections(lines: &[String]) -> Vec<Section<'_>> {
+    let mut result = Vec::new();
+    let mut start = 0;
+    for section in lines.split(|line| line.trim().is_empty()) {
+        if !section.is_empty() {
+            result.push(Section {
+                first_line: start + 1,
+                lines: section,
+            });
+        }
+        start += section.len() + 1;
+    }
+    result
+}
+
+impl<'a> Section<'a> {
+    // All the lines of the input as one section
+    pub fn new(lines: &'a [String]) -> Section<'a> {
+        Section {
+            first_line: 1,
+            lines,
+        }
+    }
+
+    // Parse the line at `idx` of the section, errors get its line number in the input
+    pub fn parse_line<T>(&self, idx: usize, parser: impl Parser<'a, T>) -> Result<T, ParseError> {
+        parse_line(&self.lines[idx], parser).map_err(|e| e.on_line(self.first_line + idx))
+    }
+
+    // Every line from `idx` on parsed with the parser
+    pub fn parse_lines_from<T>(
+        &self,
+        idx: usize,
+        parser: impl Parser<'a, T>,
+    ) -> Result<Vec<T>, ParseError> {
+        (idx..self.lines.len())
+            .map(|i| self.parse_line(i, &parser))
+            .collect()
+    }
+
+    // Block of "key: value" lines, one line for each key in their order
+    pub fn key_values<T>(
+        &self,
+        keys: &[&'static str],
+        value: impl Parser<'a, T>,
+    ) -> Result<Vec<T>, ParseError> {
+        let (values, problems) = self.key_value_lines(keys, value);
+        match problems.into_iter().next() {
+            Some(e) => Err(e),
+            None => Ok(values.into_iter().flatten().collect()),
+        }
+    }
+
+    // Like `key_values`, but going on after bad lines: the value of every key
+    // whose line parses and the problems of all the lines
+    pub fn key_value_lines<T>(
+        &self,
+        keys: &[&'static str],
+        value: impl Parser<'a, T>,
+    ) -> (Vec<Option<T>>, Vec<ParseError>) {
+        let mut values = Vec::with_capacity(keys.len());
+        let mut problems = Vec::new();
+        for (idx, &key) in keys.iter().enumerate() {
+            if idx == self.lines.len() {
+                // the keys after a missing one are missing as well
+                problems.push(
+                    ParseError::at_end("", format!("'{}:'", key)).on_line(self.first_line + idx),
+                );
+                break;
+            }
+            let line = self.parse_line(idx, preceded(pair(literal(key), literal(":")), &value));
+            values.push(line.map_err(|e| problems.push(e)).ok());
+        }
+        values.resize_with(keys.len(), || None);
+        // blank lines may follow the block, like the ones at the end of a saved input
+        let extra = (keys.len()..self.lines.len()).find(|&idx| !self.lines[idx].trim().is_empty());
+        if let Some(idx) = extra {
+            let line = &self.lines[idx];
+            problems.push(ParseError::new(line, line, "empty line").on_line(self.first_line + idx));
+        }
+        (values, problems)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(s: &str) -> Vec<String> {
+        s.lines().map(|l| l.to_string()).collect()
+    }
+
+    #[test]
+    fn test_tokens() {
+        let card = preceded(
+            pair(literal("Card"), pair(uint::<u32>, literal(":"))),
+            pair(terminated(list(uint::<u8>), literal("|")), list(uint::<u8>)),
+        );
+        assert_eq!(
+            (vec![41, 48], vec![83, 86, 6]),
+            parse_line("Card  1: 41 48 | 83 86  6", &card).unwrap()
+        );
+        assert_eq!(Ok(-12), parse_line("  -12 ", int::<i32>));
+        assert_eq!(Ok(vec![3, -1]), parse_line("3 -1", list(int::<i64>)));
+        assert_eq!(
+            Ok("blue"),
+            parse_line("blue", keyword(&["red", "green", "blue"]))
+        );
+    }
+
+    #[test]
+    fn test_positioned_errors() {
+        let game = preceded(
+            literal("Game"),
+            pair(terminated(uint::<u32>, literal(":")), list(uint::<u32>)),
+        );
+        let e = parse_line("Game x: 3", &game).unwrap_err();
+        assert_eq!(
+            (6, "number", "'x'"),
+            (e.column, e.expected.as_str(), e.found.as_str())
+        );
+        let e = parse_line("Game 1: 3 4x", &game).unwrap_err();
+        assert_eq!((12, "end of line"), (e.column, e.expected.as_str()));
+        let e = parse_line("Game 1; 3", &game).unwrap_err();
+        assert_eq!((7, "':'"), (e.column, e.expected.as_str()));
+        let e = parse_line("- 3", int::<i32>).unwrap_err();
+        assert_eq!(1, e.column);
+        let e = parse_line("99999999999", uint::<u32>).unwrap_err();
+        assert_eq!("'99999999999'", e.found);
+        // a number too big for its type does not end the list before it
+        let e = parse_line("1 300 2", list(uint::<u8>)).unwrap_err();
+        assert_eq!(
+            (3, "number", "'300'"),
+            (e.column, e.expected.as_str(), e.found.as_str())
+        );
+        let numbers = terminated(list(uint::<u8>), literal("|"));
+        let e = parse_line("41 256 | 83", &numbers).unwrap_err();
+        assert_eq!(
+            (4, "number", "'256'"),
+            (e.column, e.expected.as_str(), e.found.as_str())
+        );
+        // so does a value of several tokens which stops after its first one
+        let pairs = terminated(list(pair(uint::<u8>, word)), literal("|"));
+        let e = parse_line("1 a 2 | 3", &pairs).unwrap_err();
+        assert_eq!(
+            (7, "word", "'|'"),
+            (e.column, e.expected.as_str(), e.found.as_str())
+        );
+        assert_eq!(Ok(vec![(1, "a")]), parse_line("1 a |", &pairs));
+        let e = parse_line(" pink", keyword(&["red", "green", "blue"])).unwrap_err();
+        assert_eq!(
+            (2, "'red', 'green' or 'blue'"),
+            (e.column, e.expected.as_str())
+        );
+    }
+
+    #[test]
+    fn test_separated() {
+        let sets = separated(
+            separated(pair(uint::<u32>, word), literal(",")),
+            literal(";"),
+        );
+        let result = parse_line("3 blue, 4 red; 1 red", &sets).unwrap();
+        assert_eq!(
+            vec![vec![(3, "blue"), (4, "red")], vec![(1, "red")]],
+            result
+        );
+        assert!(parse_line("3 blue,; 1 red", &sets).is_err());
+    }
+
+    #[test]
+    fn test_sections_and_key_values() {
+        let input = lines("Time: 1 2\nDistance: 3 4\n\n\nx\ny");
+        let sections = sections(&input);
+        assert_eq!(
+            vec![(1, 2), (5, 2)],
+            sections
+                .iter()
+                .map(|s| (s.first_line, s.lines.len()))
+                .collect::<Vec<_>>()
+        );
+        let values = sections[0]
+            .key_values(&["Time", "Distance"], list(uint::<u32>))
+            .unwrap();
+        assert_eq!(vec![vec![1, 2], vec![3, 4]], values);
+        let e = sections[1].parse_lines_from(0, uint::<u32>).unwrap_err();
+        assert_eq!(5, e.line);
+        let e = sections[0]
+            .key_values(&["Time", "Distance", "Speed"], list(uint::<u32>))
+            .unwrap_err();
+        assert_eq!((3, "'Speed:'"), (e.line, e.expected.as_str()));
+        let e = sections[0]
+            .key_values(&["Time"], list(uint::<u32>))
+            .unwrap_err();
+        assert_eq!((2, "empty line"), (e.line, e.expected.as_str()));
+        let input = lines("Time: 1 2\nDistance: 3 4\n\n");
+        assert_eq!(3, input.len());
+        let values = Section::new(&input)
+            .key_values(&["Time", "Distance"], list(uint::<u32>))
+            .unwrap();
+        assert_eq!(vec![vec![1, 2], vec![3, 4]], values);
+        let input = lines("Time: 1 2\nDistance: 3 4\n\nx\ny\n");
+        let (_, problems) =
+            Section::new(&input).key_value_lines(&["Time", "Distance"], list(uint::<u32>));
+        assert_eq!(
+            vec![(4, "'x'")],
+            problems
+                .iter()
+                .map(|e| (e.line, e.found.as_str()))
+                .collect::<Vec<_>>()
+        );
+    }
+}
@@ -0,0 +1,188 @@
+use std::cell::RefCell;
+use std::io::{self, IsTerminal, Write};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+// Progress of long computations.
+// A solver reports how many units of its work are done out of a total, the runner
+// decides whether it is shown: a live line with an ETA is drawn on stderr only
+// when it is a terminal, so redirected output stays clean.
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+// The line is redrawn at most this often
+const DRAW_INTERVAL: Duration = Duration::from_millis(100);
+
+thread_local! {
+    // "<day> part<n>" of the part running on this thread
+    static LABEL: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+pub fn set_enabled(enabled: bool) {
+    ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+// Show progress only when stderr is a terminal and the output is not quieted
+pub fn enable_on_terminal(quiet: bool) {
+    set_enabled(!quiet && io::stderr().is_terminal());
+}
+
+// Stop drawing and clear the line: parts abandoned after a timeout keep running
+// and must not draw over the results
+pub fn stop() {
+    if ENABLED.swap(false, Ordering::Relaxed) {
+        eprint!("\r\x1b[K");
+    }
+}
+
+// Run `f` with the label shown in front of the progress it reports
+pub fn with_label<R>(label: String, f: impl FnOnce() -> R) -> R {
+    let previous = LABEL.with(|l| l.replace(Some(label)));
+    let result = f();
+    LABEL.with(|l| *l.borrow_mut() = previous);
+    result
+}
+
+// Progress of one computation, it may be shared by worker threads.
+// The line is cleared when it is dropped.
+pub struct Progress {
+    label: Option<String>,
+    total: u64,
+    done: AtomicU64,
+    start: Instant,
+    // milliseconds since the start at the last draw
+    last_draw: AtomicU64,
+    drawn: AtomicBool,
+}
+
+impl Progress {
+    pub fn new(total: u64) -> Progress {
+        Progress {
+            label: LABEL.with(|l| l.borrow().clone()),
+            total,
+            done: AtomicU64::new(0),
+            start: Instant::now(),
+            last_draw: AtomicU64::new(0),
+            drawn: AtomicBool::new(false),
+        }
+    }
+
+    // Report that `units` more units of work are done.
+    // It is cheap enough to be called every few thousand iterations of a hot loop.
+    pub fn add(&self, units: u64) {
+        let done = self.done.fetch_add(units, Ordering::Relaxed) + units;
+        if !ENABLED.load(Ordering::Relaxed) {
+            return;
+        }
+        let elapsed = self.start.elapsed();
+        let now = elapsed.as_millis() as u64;
+        let last = self.last_draw.load(Ordering::Relaxed);
+        if now < last + DRAW_INTERVAL.as_millis() as u64 {
+            return;
+        }
+        // only one thread draws each time
+        if self
+            .last_draw
+            .compare_exchange(last, now, Ordering::Relaxed, Ordering::Relaxed)
+            .is_ok()
+        {
+            self.drawn.store(true, Ordering::Relaxed);
+            let line = format_line(self.label.as_deref(), done, self.total, elapsed);
+            let mut stderr = io::stderr().lock();
+            let _ = write!(stderr, "\r{}\x1b[K", line);
+            let _ = stderr.flush();
+        }
+    }
+
+    pub fn done(&self) -> u64 {
+        self.done.load(Ordering::Relaxed)
+    }
+}
+
+impl Drop for Progress {
+    fn drop(&mut self) {
+        if *self.drawn.get_mut() && ENABLED.load(Ordering::Relaxed) {
+            eprint!("\r\x1b[K");
+        }
+    }
+}
+
+// Remaining time if the rest of the work goes as fast as the done part
+pub fn eta(elapsed: Duration, done: u64, total: u64) -> Option<Duration> {
+    if done == 0 {
+        return None;
+    }
+    let remaining = total.saturating_sub(done) as f64 / done as f64;
+    Some(elapsed.mul_f64(remaining))
+}
+
+fn format_duration(d: Duration) -> String {
+    let secs = d.as_secs();
+    if secs >= 3600 {
+        format!("{}h{:02}m", secs / 3600, secs % 3600 / 60)
+    } else if secs >= 60 {
+        format!("{}m{:02}s", secs / 60, secs % 60)
+    } else {
+        format!("{}s", secs)
+    }
+}
+
+fn format_line(label: Option<&str>, done: u64, total: u64, elapsed: Duration) -> String {
+    let percent = if total == 0 {
+        100.0
+    } else {
+        done as f64 * 100.0 / total as f64
+    };
+    let eta = eta(elapsed, done, total)
+        .map(format_duration)
+        .unwrap_or_else(|| "?".to_string());
+    let label = label.map(|l| format!("{}: ", l)).unwrap_or_default();
+    format!(
+        "{}{}/{} ({:.1}%), elapsed {}, ETA {}",
+        label,
+        done,
+        total,
+        percent,
+        format_duration(elapsed),
+        eta
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_eta() {
+        assert_eq!(None, eta(Duration::from_secs(1), 0, 100));
+        assert_eq!(
+            Some(Duration::from_secs(3)),
+            eta(Duration::from_secs(1), 25, 100)
+        );
+        assert_eq!(Some(Duration::ZERO), eta(Duration::from_secs(1), 100, 100));
+    }
+
+    #[test]
+    fn test_format_line() {
+        assert_eq!(
+            "day5 part2: 25/100 (25.0%), elapsed 20s, ETA 1m00s",
+            format_line(Some("day5 part2"), 25, 100, Duration::from_secs(20))
+        );
+        assert_eq!(
+            "0/7 (0.0%), elapsed 0s, ETA ?",
+            format_line(None, 0, 7, Duration::ZERO)
+        );
+    }
+
+    #[test]
+    fn test_progress_counts_across_threads() {
+        let progress = with_label("day5 part3".to_string(), || Progress::new(4000));
+        assert_eq!(Some("day5 part3"), progress.label.as_deref());
+        std::thread::scope(|scope| {
+            for _ in 0..4 {
+                scope.spawn(|| (0..1000).for_each(|_| progress.add(1)));
+            }
+        });
+        assert_eq!(4000, progress.done());
+    }
+}
@@ -0,0 +1,108 @@
+use std::ops::Range;
+
+// Small deterministic PRNG (SplitMix64) for the input generators.
+// The same seed always gives the same sequence, so an input which breaks
+// a solver is reproduced from its seed alone.
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Rng {
+        Rng { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    // Uniform number from the range, which should not be empty
+    pub fn range(&mut self, range: Range<u64>) -> u64 {
+        assert!(
+            !range.is_empty(),
+            "Random number from empty range {:?}",
+            range
+        );
+        let len = range.end - range.start;
+        // multiply-shift maps the 64 random bits onto the range without division
+        range.start + ((self.next_u64() as u128 * len as u128) >> 64) as u64
+    }
+
+    // Uniform number from the range, which should not be empty
+    pub fn range_i64(&mut self, range: Range<i64>) -> i64 {
+        assert!(
+            !range.is_empty(),
+            "Random number from empty range {:?}",
+            range
+        );
+        let len = range.end.abs_diff(range.start);
+        range.start.wrapping_add(self.range(0..len) as i64)
+    }
+
+    // Uniform index below `n`
+    pub fn below(&mut self, n: usize) -> usize {
+        self.range(0..n as u64) as usize
+    }
+
+    // True with the probability `p`
+    pub fn chance(&mut self, p: f64) -> bool {
+        // 53 random bits make a uniform float in [0, 1)
+        ((self.next_u64() >> 11) as f64 / (1u64 << 53) as f64) < p
+    }
+
+    pub fn choose<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.below(items.len())]
+    }
+
+    // Fisher-Yates shuffle
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            items.swap(i, self.below(i + 1));
+        }
+    }
+
+    // String of `len` chars chosen from the `alphabet`
+    pub fn string(&mut self, alphabet: &str, len: usize) -> String {
+        let chars: Vec<char> = alphabet.chars().collect();
+        (0..len).map(|_| *self.choose(&chars)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_same_seed_same_sequence() {
+        let first: Vec<u64> = (0..5)
+            .scan(Rng::new(42), |r, _| Some(r.next_u64()))
+            .collect();
+        let second: Vec<u64> = (0..5)
+            .scan(Rng::new(42), |r, _| Some(r.next_u64()))
+            .collect();
+        assert_eq!(first, second);
+        assert_ne!(first[0], Rng::new(43).next_u64());
+    }
+
+    #[test]
+    fn test_ranges() {
+        let mut rng = Rng::new(7);
+        let mut seen = [false; 6];
+        for _ in 0..1000 {
+            let n = rng.range(10..16);
+            assert!((10..16).contains(&n));
+            seen[n as usize - 10] = true;
+            let i = rng.range_i64(-3..3);
+            assert!((-3..3).contains(&i));
+        }
+        assert!(seen.iter().all(|s| *s));
+        let mut items: Vec<u32> = (0..20).collect();
+        rng.shuffle(&mut items);
+        items.sort();
+        assert_eq!((0..20).collect::<Vec<_>>(), items);
+    }
+}
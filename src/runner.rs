@@ -0,0 +1,549 @@
+use crate::answer::Answer;
+use crate::error::{Error, ParseError, Result};
+use crate::input;
+use crate::json::Json;
+use crate::progress;
+use crate::rng::Rng;
+use rayon::prelude::*;
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::fs;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+pub type PartFn = fn(&Path) -> Result<Answer>;
+
+// Parses the input without solving and returns every problem found in it
+pub type CheckFn = fn(&Path) -> Result<Vec<ParseError>>;
+
+// Lines of a random valid input of the given size, its meaning depends on the day
+pub type GenerateFn = fn(&mut Rng, usize) -> Vec<String>;
+
+// Official parts solve the puzzle, alternative ones solve it differently
+// (e.g. in parallel) and experimental ones explore ideas and may not solve it at all
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PartKind {
+    Official,
+    Alternative,
+    Experimental,
+}
+
+impl Display for PartKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            PartKind::Official => "official",
+            PartKind::Alternative => "alternative",
+            PartKind::Experimental => "experimental",
+        };
+        // pad, so the kind can be aligned in the catalogue
+        f.pad(s)
+    }
+}
+
+pub struct Part {
+    pub run: PartFn,
+    pub kind: PartKind,
+    pub description: &'static str,
+}
+
+pub struct Day {
+    pub title: &'static str,
+    pub check: CheckFn,
+    pub generate: GenerateFn,
+    // parts in order, part N is at index N - 1
+    pub parts: Vec<Part>,
+}
+
+pub type Days = HashMap<&'static str, Day>;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Status {
+    Ok,
+    Error,
+    Failed,
+    Unimplemented,
+    TimedOut,
+    MissingInput,
+}
+
+impl Display for Status {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            Status::Ok => "ok",
+            Status::Error => "error",
+            Status::Failed => "failed",
+            Status::Unimplemented => "unimplemented",
+            Status::TimedOut => "timed out",
+            Status::MissingInput => "missing input",
+        };
+        f.write_str(s)
+    }
+}
+
+// Outcome of running a single part of a day
+#[derive(Debug)]
+pub struct PartRun {
+    pub day: &'static str,
+    pub part: usize,
+    pub input: PathBuf,
+    pub answer: Option<Answer>,
+    pub error: Option<Error>,
+    // message of the panic which stopped a failed part
+    pub panic_message: Option<String>,
+    // total time of the run and the part of it spent loading the input
+    pub elapsed: Duration,
+    pub parse_elapsed: Duration,
+    pub status: Status,
+}
+
+impl PartRun {
+    // Why the part has no answer: the input error or the panic message
+    pub fn failure(&self) -> Option<String> {
+        match (&self.error, &self.panic_message) {
+            (Some(e), _) => Some(e.to_string()),
+            (None, Some(message)) => Some(format!("panicked: {}", message)),
+            (None, None) => None,
+        }
+    }
+
+    pub fn to_json(&self) -> Json {
+        let nanos = |d: Duration| Json::number(d.as_nanos());
+        Json::object([
+            ("day", Json::from(self.day)),
+            ("part", Json::number(self.part)),
+            ("input", Json::from(self.input.display().to_string())),
+            ("answer", Json::from(self.answer.as_ref())),
+            ("status", Json::from(self.status.to_string())),
+            ("error", Json::from(self.failure())),
+            (
+                "timings",
+                Json::object([
+                    ("parse_ns", nanos(self.parse_elapsed)),
+                    ("solve_ns", nanos(self.elapsed - self.parse_elapsed)),
+                    ("total_ns", nanos(self.elapsed)),
+                ]),
+            ),
+        ])
+    }
+}
+
+// Days sorted by their number, so "day10" goes after "day9"
+pub fn sorted_days(days: &Days) -> Vec<&'static str> {
+    let mut names: Vec<&'static str> = days.keys().cloned().collect();
+    names.sort_by_key(|name| day_number(name));
+    names
+}
+
+fn day_number(name: &str) -> u32 {
+    name.trim_start_matches("day").parse().unwrap_or(u32::MAX)
+}
+
+// Parts are numbered from 1 and may be written either as "2" or as "part2"
+pub fn parse_part_number(s: &str) -> Option<usize> {
+    s.strip_prefix("part")
+        .unwrap_or(s)
+        .parse::<usize>()
+        .ok()
+        .filter(|p| *p > 0)
+}
+
+// By convention the real input of a day lives in <inputs_dir>/<day>.txt
+pub fn input_path(inputs_dir: &Path, day: &str) -> PathBuf {
+    inputs_dir.join(format!("{}.txt", day))
+}
+
+// By convention the examples of a day live in <examples_dir>/<day><n>.test,
+// e.g. day52.test is the second example of day5
+pub fn example_path(examples_dir: &Path, day: &str, n: usize) -> PathBuf {
+    examples_dir.join(format!("{}{}.test", day, n))
+}
+
+// Day of an example file named by the convention above. Names like day111.test are ambiguous,
+// so the longest registered day name wins: it is the first example of day11, not the 11th of day1.
+pub fn example_day(days: &Days, file_name: &str) -> Option<&'static str> {
+    let stem = file_name.strip_suffix(".test")?;
+    days.keys()
+        .copied()
+        .filter(|day| {
+            stem.strip_prefix(day)
+                .is_some_and(|n| !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()))
+        })
+        .max_by_key(|day| day.len())
+}
+
+// Input and example files in `dir` which look like they belong to the day, sorted by name
+pub fn candidate_inputs(dir: &Path, day: &str) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut result: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file())
+        .filter(|path| {
+            matches!(
+                path.extension().and_then(|e| e.to_str()),
+                Some("txt" | "test")
+            )
+        })
+        .filter(|path| {
+            let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+            name.strip_prefix(day)
+                .and_then(|rest| rest.chars().next())
+                .is_some_and(|c| c.is_ascii_digit() || c == '.')
+        })
+        .collect();
+    result.sort();
+    result
+}
+
+pub fn run_part(day: &'static str, part: usize, part_fn: PartFn, path: &Path) -> PartRun {
+    input::take_parse_time();
+    let start = Instant::now();
+    let label = format!("{} part{}", day, part);
+    let result = progress::with_label(label, || {
+        panic::catch_unwind(AssertUnwindSafe(|| part_fn(path)))
+    });
+    let elapsed = start.elapsed();
+    let parse_elapsed = input::take_parse_time().min(elapsed);
+    let (mut answer, mut error, mut panic_message) = (None, None, None);
+    let status = match result {
+        Ok(Ok(a)) => {
+            answer = Some(a);
+            Status::Ok
+        }
+        Ok(Err(e)) => {
+            error = Some(e.in_file(path));
+            Status::Error
+        }
+        Err(payload) => {
+            let message = payload
+                .downcast_ref::<&str>()
+                .map(|s| s.to_string())
+                .or_else(|| payload.downcast_ref::<String>().cloned())
+                .unwrap_or_default();
+            // todo!() and unimplemented!() panic with these fixed prefixes
+            if message.starts_with("not yet implemented") || message.starts_with("not implemented")
+            {
+                Status::Unimplemented
+            } else {
+                panic_message = Some(message);
+                Status::Failed
+            }
+        }
+    };
+    PartRun {
+        day,
+        part,
+        input: path.to_path_buf(),
+        answer,
+        error,
+        panic_message,
+        elapsed,
+        parse_elapsed,
+        status,
+    }
+}
+
+// Run the part on a worker thread and give up waiting for it after the timeout.
+// Threads can't be killed, so a timed out part keeps running in the background until the process exits.
+pub fn run_part_with_timeout(
+    day: &'static str,
+    part: usize,
+    part_fn: PartFn,
+    path: &Path,
+    timeout: Option<Duration>,
+) -> PartRun {
+    let Some(timeout) = timeout else {
+        return run_part(day, part, part_fn, path);
+    };
+    let (sender, receiver) = mpsc::channel();
+    let worker_path = path.to_path_buf();
+    thread::spawn(move || {
+        // the receiver is gone when we time out, nobody needs the result then
+        let _ = sender.send(run_part(day, part, part_fn, &worker_path));
+    });
+    match receiver.recv_timeout(timeout) {
+        Ok(run) => run,
+        Err(RecvTimeoutError::Disconnected) => unreachable!("run_part catches panics"),
+        Err(RecvTimeoutError::Timeout) => PartRun {
+            day,
+            part,
+            input: path.to_path_buf(),
+            answer: None,
+            error: None,
+            panic_message: None,
+            elapsed: timeout,
+            parse_elapsed: Duration::ZERO,
+            status: Status::TimedOut,
+        },
+    }
+}
+
+// Map items on `threads` plain threads, keeping the order of the items
+fn map_on_threads<T, R, F>(items: Vec<T>, threads: usize, f: F) -> Vec<R>
+where
+    T: Send,
+    R: Send,
+    F: Fn(T) -> R + Sync,
+{
+    let count = items.len();
+    let queue = Mutex::new(items.into_iter().enumerate());
+    let results = Mutex::new((0..count).map(|_| None).collect::<Vec<Option<R>>>());
+    thread::scope(|scope| {
+        for _ in 0..threads.min(count) {
+            scope.spawn(|| loop {
+                let next = queue.lock().unwrap().next();
+                let Some((idx, item)) = next else {
+                    break;
+                };
+                let result = f(item);
+                results.lock().unwrap()[idx] = Some(result);
+            });
+        }
+    });
+    results
+        .into_inner()
+        .unwrap()
+        .into_iter()
+        .map(|r| r.expect("Every item is mapped"))
+        .collect()
+}
+
+// Accepts plain seconds ("1.5") or a number with a unit ("500ms", "2s", "1m")
+pub fn parse_duration(s: &str) -> Option<Duration> {
+    let (number, scale) = if let Some(n) = s.strip_suffix("ms") {
+        (n, 0.001)
+    } else if let Some(n) = s.strip_suffix('s') {
+        (n, 1.0)
+    } else if let Some(n) = s.strip_suffix('m') {
+        (n, 60.0)
+    } else {
+        (s, 1.0)
+    };
+    let secs = number.parse::<f64>().ok()? * scale;
+    (secs.is_finite() && secs > 0.0).then(|| Duration::from_secs_f64(secs))
+}
+
+// Run every part of every registered day on its conventional input.
+// Parts run concurrently, on the global rayon pool without a timeout,
+// but the result keeps the order of days and parts.
+pub fn run_all(days: &Days, inputs_dir: &Path, timeout: Option<Duration>) -> Vec<PartRun> {
+    // panics are reported in the table, so we silence the default hook which prints them to stderr
+    let default_hook = panic::take_hook();
+    panic::set_hook(Box::new(|_| {}));
+    let tasks: Vec<(&'static str, usize, PartFn)> = sorted_days(days)
+        .into_iter()
+        .flat_map(|day| {
+            days[day]
+                .parts
+                .iter()
+                .enumerate()
+                .map(move |(idx, part)| (day, idx + 1, part.run))
+        })
+        .collect();
+    let run = |(day, part, part_fn): (&'static str, usize, PartFn)| {
+        let path = input_path(inputs_dir, day);
+        if path.is_file() {
+            run_part_with_timeout(day, part, part_fn, &path, timeout)
+        } else {
+            PartRun {
+                day,
+                part,
+                input: path,
+                answer: None,
+                error: None,
+                panic_message: None,
+                elapsed: Duration::ZERO,
+                parse_elapsed: Duration::ZERO,
+                status: Status::MissingInput,
+            }
+        }
+    };
+    let runs = match timeout {
+        None => tasks.into_par_iter().map(run).collect(),
+        // waiting for a worker would block a pool thread which parallel parts may need,
+        // so with a timeout the parts are scheduled from plain threads, as many as the pool has
+        Some(_) => map_on_threads(tasks, rayon::current_num_threads(), run),
+    };
+    panic::set_hook(default_hook);
+    runs
+}
+
+pub fn print_table(runs: &[PartRun]) {
+    let answers: Vec<String> = runs
+        .iter()
+        .map(|r| r.answer.as_ref().map(|a| a.to_string()).unwrap_or_default())
+        .collect();
+    let answer_width = answers.iter().map(|a| a.len()).max().unwrap_or(0).max(6);
+    println!(
+        "{:<6} {:<5} {:<aw$} {:>12} status",
+        "day",
+        "part",
+        "answer",
+        "time",
+        aw = answer_width
+    );
+    for (run, answer) in runs.iter().zip(answers) {
+        let elapsed = match run.status {
+            Status::MissingInput => "-".to_string(),
+            _ => format!("{:.3?}", run.elapsed),
+        };
+        println!(
+            "{:<6} {:<5} {:<aw$} {:>12} {}",
+            run.day,
+            run.part,
+            answer,
+            elapsed,
+            run.status,
+            aw = answer_width
+        );
+    }
+    // input errors and panic messages are too long for the table, so we show them after it
+    for run in runs {
+        if let Some(failure) = run.failure() {
+            crate::error!("{} part{}: {}", run.day, run.part, failure);
+        }
+    }
+}
+
+pub fn print_json(runs: &[PartRun]) {
+    println!(
+        "{}",
+        Json::Array(runs.iter().map(PartRun::to_json).collect())
+    );
+}
+
+// Catalogue of all registered days and their parts
+pub fn print_list(days: &Days) {
+    for day in sorted_days(days) {
+        println!("{:<6} {}", day, days[day].title);
+        for (idx, part) in days[day].parts.iter().enumerate() {
+            println!(
+                "  part{:<2} {:<12} {}",
+                idx + 1,
+                part.kind,
+                part.description
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ok_part(_: &Path) -> Result<Answer> {
+        Ok(42.into())
+    }
+
+    fn todo_part(_: &Path) -> Result<Answer> {
+        todo!()
+    }
+
+    fn failing_part(_: &Path) -> Result<Answer> {
+        panic!("broken")
+    }
+
+    fn bad_input_part(path: &Path) -> Result<Answer> {
+        crate::input::parse_grid(crate::input::open(path)?, ".")?;
+        Ok(0.into())
+    }
+
+    #[test]
+    fn test_run_part_status() {
+        let path = Path::new("day11.test");
+        let run = run_part("day1", 1, ok_part, path);
+        assert_eq!(Status::Ok, run.status);
+        assert_eq!(Some(Answer::Int(42)), run.answer);
+        assert_eq!(
+            Status::Unimplemented,
+            run_part("day1", 2, todo_part, path).status
+        );
+        let run = run_part("day1", 3, failing_part, path);
+        assert_eq!(Status::Failed, run.status);
+        assert_eq!(Some("broken".to_string()), run.panic_message);
+        let run = run_part("day1", 4, bad_input_part, path);
+        assert_eq!(Status::Error, run.status);
+        match run.error {
+            Some(Error::Parse(e)) => {
+                assert_eq!(Some(path.to_path_buf()), e.file);
+                assert_eq!((1, 1), (e.line, e.column));
+            }
+            e => panic!("Expected parse error, got {:?}", e),
+        }
+    }
+
+    #[test]
+    fn test_candidate_inputs() {
+        let candidates = candidate_inputs(Path::new("."), "day8");
+        let expected: Vec<PathBuf> = ["day81.test", "day82.test", "day83.test"]
+            .iter()
+            .map(|name| Path::new(".").join(name))
+            .collect();
+        assert_eq!(expected, candidates);
+        assert_eq!(
+            Path::new("./day52.test"),
+            example_path(Path::new("."), "day5", 2)
+        );
+    }
+
+    #[test]
+    fn test_example_day() {
+        let days = crate::days();
+        assert_eq!(Some("day1"), example_day(&days, "day11.test"));
+        assert_eq!(Some("day11"), example_day(&days, "day111.test"));
+        assert_eq!(Some("day10"), example_day(&days, "day104.test"));
+        assert_eq!(None, example_day(&days, "day5.txt"));
+        assert_eq!(None, example_day(&days, "day5x.test"));
+    }
+
+    fn slow_part(_: &Path) -> Result<Answer> {
+        thread::sleep(Duration::from_secs(2));
+        Ok(0.into())
+    }
+
+    #[test]
+    fn test_run_part_with_timeout() {
+        let path = Path::new("day11.test");
+        let timeout = Some(Duration::from_millis(50));
+        let run = run_part_with_timeout("day1", 1, slow_part, path, timeout);
+        assert_eq!(Status::TimedOut, run.status);
+        let run = run_part_with_timeout("day1", 1, ok_part, path, timeout);
+        assert_eq!(Some(Answer::Int(42)), run.answer);
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(Some(Duration::from_millis(1500)), parse_duration("1.5"));
+        assert_eq!(Some(Duration::from_millis(500)), parse_duration("500ms"));
+        assert_eq!(Some(Duration::from_secs(2)), parse_duration("2s"));
+        assert_eq!(Some(Duration::from_secs(60)), parse_duration("1m"));
+        assert_eq!(None, parse_duration("0"));
+        assert_eq!(None, parse_duration("soon"));
+    }
+
+    #[test]
+    fn test_sorted_days() {
+        let day = |title| Day {
+            title,
+            check: |_| Ok(Vec::new()),
+            generate: |_, _| Vec::new(),
+            parts: vec![Part {
+                run: ok_part,
+                kind: PartKind::Official,
+                description: "",
+            }],
+        };
+        let days: Days = HashMap::from([
+            ("day10", day("Pipe Maze")),
+            ("day9", day("Mirage Maintenance")),
+            ("day1", day("Trebuchet?!")),
+        ]);
+        assert_eq!(vec!["day1", "day9", "day10"], sorted_days(&days));
+    }
+}
@@ -0,0 +1,151 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+// Source of a new day, it is compiled with the tests so it never rots
+const TEMPLATE: &str = include_str!("template.rs");
+
+// Day name from "20" or "day20"
+pub fn parse_day(s: &str) -> Option<String> {
+    let n: u32 = s.strip_prefix("day").unwrap_or(s).parse().ok()?;
+    (1..=25).contains(&n).then(|| format!("day{}", n))
+}
+
+// Create <root>/src/<day>.rs from the template, register it in <root>/src/lib.rs
+// and create an empty first example <root>/<day>1.test.
+// Returns the created files.
+pub fn new_day(root: &Path, day: &str) -> io::Result<Vec<PathBuf>> {
+    let module_path = root.join("src").join(format!("{}.rs", day));
+    if module_path.exists() {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!("{} already exists", module_path.display()),
+        ));
+    }
+    let lib_path = root.join("src").join("lib.rs");
+    let lib_src = fs::read_to_string(&lib_path)?;
+    let lib_src = register_day(&lib_src, day).ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("could not find the day registry in {}", lib_path.display()),
+        )
+    })?;
+    fs::write(&module_path, TEMPLATE)?;
+    fs::write(&lib_path, lib_src)?;
+    let example_path = root.join(format!("{}1.test", day));
+    if !example_path.exists() {
+        fs::write(&example_path, "")?;
+    }
+    Ok(vec![module_path, example_path])
+}
+
+fn day_number(line: &str, prefix: &str) -> Option<u32> {
+    let rest = line.trim_start().strip_prefix(prefix)?;
+    let digits: String = rest.chars().take_while(|c| c.is_ascii_digit()).collect();
+    digits.parse().ok()
+}
+
+// Add `pub mod <day>;` to the module list and an entry with two parts to `gen_days!`.
+// Both keep their order: modules are sorted by name, registry entries by day number.
+pub fn register_day(lib_src: &str, day: &str) -> Option<String> {
+    let number = day_number(day, "day")?;
+    let mut lines: Vec<String> = lib_src.lines().map(|l| l.to_string()).collect();
+
+    let module_line = format!("pub mod {};", day);
+    let mod_positions: Vec<usize> = (0..lines.len())
+        .filter(|&i| lines[i].starts_with("pub mod day"))
+        .collect();
+    let last_mod = *mod_positions.last()?;
+    let mod_idx = mod_positions
+        .iter()
+        .copied()
+        .find(|&i| lines[i].trim_end_matches(';') > module_line.trim_end_matches(';'))
+        .unwrap_or(last_mod + 1);
+    lines.insert(mod_idx, module_line);
+
+    // registry entries start with "(dayN," and end with "),"
+    // except the last one, which has no trailing comma
+    let entry_starts: Vec<usize> = (0..lines.len())
+        .filter(|&i| day_number(&lines[i], "(day").is_some())
+        .collect();
+    let last_start = *entry_starts.last()?;
+    let entry = [
+        format!("        ({}, \"Day {}\",", day, number),
+        "            (part1, Official, \"Not solved yet\"),".to_string(),
+        "            (part2, Official, \"Not solved yet\"))".to_string(),
+    ];
+    match entry_starts
+        .iter()
+        .copied()
+        .find(|&i| day_number(&lines[i], "(day") > Some(number))
+    {
+        Some(idx) => {
+            let mut entry = entry.to_vec();
+            entry[2].push(',');
+            lines.splice(idx..idx, entry);
+        }
+        None => {
+            let last_end = (last_start..lines.len()).find(|&i| lines[i].ends_with("))"))?;
+            lines[last_end].push(',');
+            lines.splice(last_end + 1..last_end + 1, entry);
+        }
+    }
+    let mut result = lines.join("\n");
+    result.push('\n');
+    Some(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LIB: &str = "\
+pub mod answer;
+pub mod day1;
+pub mod day10;
+pub mod day9;
+pub mod error;
+
+pub fn days() -> runner::Days {
+    gen_days! {
+        (day1, \"Trebuchet?!\",
+            (part1, Official, \"first\"),
+            (part2, Official, \"second\")),
+        (day9, \"Mirage Maintenance\",
+            (part1, Official, \"first\")),
+        (day10, \"Pipe Maze\",
+            (part1, Official, \"first\"))
+    }
+}
+";
+
+    #[test]
+    fn test_parse_day() {
+        assert_eq!(Some("day20".to_string()), parse_day("20"));
+        assert_eq!(Some("day7".to_string()), parse_day("day7"));
+        assert_eq!(None, parse_day("day26"));
+        assert_eq!(None, parse_day("x"));
+    }
+
+    #[test]
+    fn test_register_day_in_the_middle() {
+        let result = register_day(LIB, "day2").unwrap();
+        assert!(result.contains("pub mod day10;\npub mod day2;\npub mod day9;\n"));
+        assert!(result.contains(
+            "(part2, Official, \"second\")),\n        (day2, \"Day 2\",\n            \
+             (part1, Official, \"Not solved yet\"),\n            \
+             (part2, Official, \"Not solved yet\")),\n        (day9,"
+        ));
+    }
+
+    #[test]
+    fn test_register_day_at_the_end() {
+        let result = register_day(LIB, "day20").unwrap();
+        assert!(result.contains("pub mod day10;\npub mod day20;\npub mod day9;\n"));
+        assert!(result.contains(
+            "(part1, Official, \"first\")),\n        (day20, \"Day 20\",\n            \
+             (part1, Official, \"Not solved yet\"),\n            \
+             (part2, Official, \"Not solved yet\"))\n    }"
+        ));
+    }
+}
@@ -1,19 +1,46 @@
-use std::fs::File;
-use std::io::{self, BufRead};
+use crate::answer::Answer;
+use crate::error::{ParseError, Result};
+use crate::input;
+use crate::rng::Rng;
+use std::io::BufRead;
 use std::path::Path;
 
-pub fn part1(input_path: &Path) {
+#[allow(unused)]
+pub fn part1(input_path: &Path) -> Result<Answer> {
+    let input = load_input(input_path)?;
     todo!()
 }
 
 #[allow(unused)]
-pub fn part2(input_path: &Path) {
+pub fn part2(input_path: &Path) -> Result<Answer> {
+    let input = load_input(input_path)?;
     todo!()
 }
 
-fn load_input(input_path: &Path) -> io::Result<_> {
-    let file = File::open(input_path)?;
-    let buf_reader = io::BufReader::new(file);
-    let mut lines = buf_reader.lines();
-    todo!()
+pub type Input = Vec<String>;
+
+// `size` lines of random words, replace them with valid inputs of the day
+pub fn generate(rng: &mut Rng, size: usize) -> Vec<String> {
+    (0..size)
+        .map(|_| {
+            let len = rng.range(1..10) as usize;
+            rng.string("abcdefghijklmnopqrstuvwxyz", len)
+        })
+        .collect()
+}
+
+pub fn check(input_path: &Path) -> Result<Vec<ParseError>> {
+    input::check_lines(input_path, parse_line)
+}
+
+pub fn load_input(input_path: &Path) -> Result<Input> {
+    input::load(input_path, parse_input)
+}
+
+pub fn parse_input(reader: impl BufRead) -> Result<Input> {
+    input::parse_lines(reader, parse_line)
+}
+
+fn parse_line(line: &str) -> Result<String, ParseError> {
+    Ok(line.to_string())
 }
@@ -0,0 +1,224 @@
+use crate::runner::{self, Days, Status};
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{self, BufRead};
+use std::panic;
+use std::path::{Path, PathBuf};
+
+// Expected answer of one part on one input, as written in the answers file:
+//   <day> <part> <input> <answer>
+// <input> is a path to an input file, or "input" for the conventional real input of the day.
+// The answer takes the rest of the line, so text answers may contain spaces.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Expectation {
+    pub day: String,
+    pub part: usize,
+    pub input: String,
+    pub answer: String,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Outcome {
+    Pass,
+    Mismatch(String),
+    // status of the part and why it failed
+    Failed(Status, Option<String>),
+    Skipped(String),
+}
+
+pub fn load_expectations(path: &Path) -> io::Result<Vec<Expectation>> {
+    let file = File::open(path)?;
+    let buf_reader = io::BufReader::new(file);
+    let mut result = Vec::new();
+    for (idx, line) in buf_reader.lines().enumerate() {
+        let line = line?;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let expectation = parse_expectation(line).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "{}:{}: malformed answer line '{}'",
+                    path.display(),
+                    idx + 1,
+                    line
+                ),
+            )
+        })?;
+        result.push(expectation);
+    }
+    Ok(result)
+}
+
+// Example input of a day with the expected answers of its parts,
+// which are kept in the sidecar file <day><n>.answers as lines of <part> <answer>
+#[derive(Debug)]
+pub struct Example {
+    pub day: &'static str,
+    pub path: PathBuf,
+    pub answers: HashMap<usize, String>,
+}
+
+// All example files in `dir` which belong to a registered day, sorted by name
+pub fn find_examples(days: &Days, dir: &Path) -> io::Result<Vec<Example>> {
+    let mut result = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+        let Some(day) = runner::example_day(days, name) else {
+            continue;
+        };
+        let sidecar = path.with_extension("answers");
+        let answers = if sidecar.is_file() {
+            load_sidecar(&sidecar)?
+        } else {
+            HashMap::new()
+        };
+        result.push(Example { day, path, answers });
+    }
+    result.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(result)
+}
+
+fn load_sidecar(path: &Path) -> io::Result<HashMap<usize, String>> {
+    let mut result = HashMap::new();
+    for (idx, line) in fs::read_to_string(path)?.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let answer = line
+            .split_once(char::is_whitespace)
+            .and_then(|(part, answer)| {
+                Some((runner::parse_part_number(part)?, answer.trim().to_string()))
+            });
+        let Some((part, answer)) = answer.filter(|(_, a)| !a.is_empty()) else {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "{}:{}: malformed answer line '{}'",
+                    path.display(),
+                    idx + 1,
+                    line
+                ),
+            ));
+        };
+        result.insert(part, answer);
+    }
+    Ok(result)
+}
+
+// Expected answers of the examples in `dir`, in the order of files and parts
+pub fn example_expectations(days: &Days, dir: &Path) -> io::Result<Vec<Expectation>> {
+    let mut result = Vec::new();
+    for example in find_examples(days, dir)? {
+        let mut answers: Vec<_> = example.answers.into_iter().collect();
+        answers.sort();
+        for (part, answer) in answers {
+            result.push(Expectation {
+                day: example.day.to_string(),
+                part,
+                input: example.path.display().to_string(),
+                answer,
+            });
+        }
+    }
+    Ok(result)
+}
+
+fn parse_expectation(line: &str) -> Option<Expectation> {
+    let mut splits = line.splitn(4, char::is_whitespace);
+    let day = splits.next()?.to_string();
+    let part = runner::parse_part_number(splits.next()?)?;
+    let input = splits.next()?.to_string();
+    let answer = splits.next()?.trim().to_string();
+    if answer.is_empty() {
+        return None;
+    }
+    Some(Expectation {
+        day,
+        part,
+        input,
+        answer,
+    })
+}
+
+fn resolve_input(expectation: &Expectation, inputs_dir: &Path) -> PathBuf {
+    if expectation.input == "input" {
+        runner::input_path(inputs_dir, &expectation.day)
+    } else {
+        PathBuf::from(&expectation.input)
+    }
+}
+
+pub fn check(days: &Days, expectation: &Expectation, inputs_dir: &Path) -> Outcome {
+    let Some((&day, entry)) = days.get_key_value(expectation.day.as_str()) else {
+        return Outcome::Skipped(format!("unknown day {}", expectation.day));
+    };
+    if expectation.part == 0 || expectation.part > entry.parts.len() {
+        return Outcome::Skipped(format!("{} has no part {}", day, expectation.part));
+    }
+    let path = resolve_input(expectation, inputs_dir);
+    if !path.is_file() {
+        return Outcome::Skipped(format!("missing input {}", path.display()));
+    }
+    let part_fn = entry.parts[expectation.part - 1].run;
+    let run = runner::run_part(day, expectation.part, part_fn, &path);
+    match run.answer {
+        Some(answer) if answer.to_string() == expectation.answer => Outcome::Pass,
+        Some(answer) => Outcome::Mismatch(answer.to_string()),
+        None => Outcome::Failed(run.status.clone(), run.failure()),
+    }
+}
+
+// Check every expectation and print a report line for each of them.
+// Returns true if no answer differs from the expected one and no part failed.
+pub fn verify(days: &Days, expectations: &[Expectation], inputs_dir: &Path) -> bool {
+    let default_hook = panic::take_hook();
+    panic::set_hook(Box::new(|_| {}));
+    let mut failures = 0;
+    for e in expectations {
+        let outcome = check(days, e, inputs_dir);
+        let report = match &outcome {
+            Outcome::Pass => "ok".to_string(),
+            Outcome::Mismatch(got) => format!("MISMATCH: expected {}, got {}", e.answer, got),
+            Outcome::Failed(status, None) => format!("FAILED: {}", status),
+            // only the first line, diagnostics of input errors take several
+            Outcome::Failed(status, Some(failure)) => format!(
+                "FAILED: {}: {}",
+                status,
+                failure.lines().next().unwrap_or("")
+            ),
+            Outcome::Skipped(reason) => format!("skipped: {}", reason),
+        };
+        if matches!(outcome, Outcome::Mismatch(_) | Outcome::Failed(..)) {
+            failures += 1;
+        }
+        println!("{:<6} part{:<2} {:<20} {}", e.day, e.part, e.input, report);
+    }
+    panic::set_hook(default_hook);
+    println!("{} checked, {} failed", expectations.len(), failures);
+    failures == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_expectation() {
+        assert_eq!(
+            Some(Expectation {
+                day: "day8".to_string(),
+                part: 3,
+                input: "day83.test".to_string(),
+                answer: "11Z@2 22Z@3".to_string(),
+            }),
+            parse_expectation("day8 part3 day83.test 11Z@2 22Z@3")
+        );
+        assert_eq!(None, parse_expectation("day8 3 day83.test"));
+        assert_eq!(None, parse_expectation("day8 x day83.test 1"));
+    }
+}
@@ -0,0 +1,82 @@
+// Golden tests: every registered part of a day runs on every example file of the day
+// and its answer is compared with the one in the sidecar <day><n>.answers file.
+// Parts without an expected answer are reported as ignored.
+use std::panic;
+use std::path::Path;
+use std::process::exit;
+
+use rust_aoc_2023::runner::{self, Status};
+use rust_aoc_2023::{days, verify};
+
+fn main() {
+    let days = days();
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let examples = verify::find_examples(&days, root).expect("Could not load the examples");
+    // arguments other than flags filter tests by name, like libtest does
+    let filters: Vec<String> = std::env::args()
+        .skip(1)
+        .filter(|a| !a.starts_with('-'))
+        .collect();
+
+    let default_hook = panic::take_hook();
+    panic::set_hook(Box::new(|_| {}));
+    let (mut passed, mut failed, mut ignored) = (0, 0, 0);
+    let mut failures = Vec::new();
+    for example in &examples {
+        let file_name = example.path.file_name().unwrap().to_string_lossy();
+        for (idx, part) in days[example.day].parts.iter().enumerate() {
+            let name = format!("{} part{}", file_name, idx + 1);
+            if !filters.is_empty() && !filters.iter().any(|f| name.contains(f.as_str())) {
+                continue;
+            }
+            let Some(expected) = example.answers.get(&(idx + 1)) else {
+                println!("test {} ... ignored", name);
+                ignored += 1;
+                continue;
+            };
+            let run = runner::run_part(example.day, idx + 1, part.run, &example.path);
+            let outcome = match (&run.status, &run.answer) {
+                (Status::Ok, Some(answer)) if answer.to_string() == *expected => None,
+                (Status::Ok, Some(answer)) => {
+                    Some(format!("expected {}, got {}", expected, answer))
+                }
+                _ => Some(match run.failure() {
+                    Some(failure) => format!(
+                        "expected {}, the part {}: {}",
+                        expected, run.status, failure
+                    ),
+                    None => format!("expected {}, the part {}", expected, run.status),
+                }),
+            };
+            match outcome {
+                None => {
+                    println!("test {} ... ok", name);
+                    passed += 1;
+                }
+                Some(message) => {
+                    println!("test {} ... FAILED", name);
+                    failures.push((name, message));
+                    failed += 1;
+                }
+            }
+        }
+    }
+    panic::set_hook(default_hook);
+
+    if !failures.is_empty() {
+        println!("\nfailures:");
+        for (name, message) in &failures {
+            println!("    {}: {}", name, message);
+        }
+    }
+    println!(
+        "\ntest result: {}. {} passed; {} failed; {} ignored",
+        if failed == 0 { "ok" } else { "FAILED" },
+        passed,
+        failed,
+        ignored
+    );
+    if failed > 0 {
+        exit(1);
+    }
+}
@@ -0,0 +1,52 @@
+// Every generator emits inputs which follow the grammar of its day
+// and which part 1 of the day can solve
+use std::fs;
+
+use rust_aoc_2023::rng::Rng;
+use rust_aoc_2023::runner::Status;
+use rust_aoc_2023::{days, runner};
+
+#[test]
+fn test_generated_inputs_pass_check() {
+    let days = days();
+    let dir = std::env::temp_dir().join(format!("rust-aoc-generators-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    for day in runner::sorted_days(&days) {
+        for (seed, size) in [(1, 1), (2, 3), (3, 12), (4, 40)] {
+            let lines = (days[day].generate)(&mut Rng::new(seed), size);
+            let path = dir.join(format!("{}-{}.txt", day, seed));
+            fs::write(&path, lines.join("\n") + "\n").unwrap();
+            let problems = (days[day].check)(&path).unwrap();
+            if let Some(problem) = problems.first() {
+                panic!("{} input from seed {}:\n{}", day, seed, problem);
+            }
+            // the bigger inputs are left to the diff tests, part 1 may be brute force
+            if size <= 12 {
+                let run = runner::run_part(day, 1, days[day].parts[0].run, &path);
+                assert!(
+                    matches!(run.status, Status::Ok | Status::Unimplemented),
+                    "{} part1 on input from seed {}: {} {}",
+                    day,
+                    seed,
+                    run.status,
+                    run.failure().unwrap_or_default()
+                );
+            }
+        }
+    }
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_same_seed_same_input() {
+    let days = days();
+    for day in runner::sorted_days(&days) {
+        let generate = days[day].generate;
+        assert_eq!(
+            generate(&mut Rng::new(9), 10),
+            generate(&mut Rng::new(9), 10),
+            "{}",
+            day
+        );
+    }
+}